@@ -1,14 +1,158 @@
-use bevy::prelude::*;
-use bevy_kira_audio::prelude::{Audio, *};
+//! Music and sounds of the game.
+//!
+//! Every kind of sound has its own channel, so its volume can be set separately. Whenever the
+//! state changes, the music of the old state fades out while the music of the new one fades in.
+//! The music is ducked while a voice line plays.
+//!
+//! During a floor, a more intense variant of the track plays along in sync. It is mixed in as
+//! more co-workers get close to the tower.
+//!
+//! Sound effects are played through `SoundEffects`, which pans and attenuates them based on where
+//! they happen relative to the camera.
+//!
 
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_kira_audio::prelude::*;
+use std::time::Duration;
+
+use crate::camera::PLAY_AREA;
+use crate::enemies::Enemy;
 use crate::game::GameState;
 use crate::level::Level;
+use crate::physics::{Moving, MovingState};
+use crate::settings::{Settings, Sound};
+use crate::tower::Tower;
+
+/// Volume of the music while a voice line plays, relative to its normal volume.
+const DUCKED_VOLUME: f64 = 0.3;
+const DUCKING_DURATION: Duration = Duration::from_millis(300);
+const CROSSFADE_DURATION: Duration = Duration::from_millis(1500);
+
+/// Co-workers within this distance of the tower make the music more intense.
+const INTENSE_RADIUS: f32 = 300.0;
+/// Amount of nearby co-workers at which only the intense variant is heard.
+const INTENSE_COUNT: f32 = 5.0;
+/// How fast the intensity follows the amount of nearby co-workers, per second.
+const INTENSITY_RATE: f64 = 0.5;
+
+/// Distance from the camera at which a sound effect is heard at half volume.
+const HALF_VOLUME_DISTANCE: f32 = 700.0;
+/// How far sound effects are panned at the edges of the screen, from 0.0 (not) to 1.0 (fully).
+const STEREO_WIDTH: f32 = 0.8;
+
+#[derive(Resource)]
+pub struct MusicChannel;
+
+/// Channel for sound effects, like placing an item and co-workers mumbling.
+#[derive(Resource)]
+pub struct SfxChannel;
+
+/// Channel for the voice lines of dialogues.
+#[derive(Resource)]
+pub struct VoiceChannel;
+
+/// The music which is currently playing.
+///
+/// The volume of music is set per instance instead of on the channel, so the old and new track
+/// can be faded independently.
+#[derive(Default, Resource)]
+struct Music {
+    track: Option<Handle<AudioInstance>>,
+    /// Variant of the track which plays in sync with it.
+    intense: Option<Handle<AudioInstance>>,
+    /// How much of the intense variant is heard, from 0.0 to 1.0.
+    intensity: f64,
+    /// Volume of the music, before the intensity is applied.
+    volume: f64,
+}
+
+impl Music {
+    fn play(
+        &mut self,
+        channel: &AudioChannel<MusicChannel>,
+        track: Handle<AudioSource>,
+        intense: Option<Handle<AudioSource>>,
+    ) {
+        let volume = self.volume;
+        let play = |source, volume| {
+            channel
+                .play(source)
+                .with_volume(volume)
+                .fade_in(AudioTween::linear(CROSSFADE_DURATION))
+                .looped()
+                .handle()
+        };
+
+        self.intensity = 0.0;
+        self.track = Some(play(track, volume));
+        self.intense = intense.map(|intense| play(intense, 0.0));
+    }
+
+    fn fade_out(&mut self, instances: &mut Assets<AudioInstance>) {
+        for handle in [self.track.take(), self.intense.take()]
+            .into_iter()
+            .flatten()
+        {
+            if let Some(instance) = instances.get_mut(&handle) {
+                instance.stop(AudioTween::linear(CROSSFADE_DURATION));
+            }
+        }
+    }
+
+    fn apply_volume(&self, instances: &mut Assets<AudioInstance>) {
+        let layers = [
+            (&self.track, 1.0 - self.intensity),
+            (&self.intense, self.intensity),
+        ];
+
+        for (handle, share) in layers {
+            if let Some(instance) = handle.as_ref().and_then(|handle| instances.get_mut(handle)) {
+                instance.set_volume(self.volume * share, AudioTween::linear(DUCKING_DURATION));
+            }
+        }
+    }
+}
+
+/// Plays sound effects at a position in the world.
+#[derive(SystemParam)]
+pub struct SoundEffects<'w, 's> {
+    channel: Res<'w, AudioChannel<SfxChannel>>,
+    settings: Res<'w, Settings>,
+    camera_query: Query<'w, 's, &'static GlobalTransform, With<Camera>>,
+}
+
+impl<'w, 's> SoundEffects<'w, 's> {
+    pub fn play_at(&self, source: Handle<AudioSource>, position: Vec3) {
+        let listener = self
+            .camera_query
+            .get_single()
+            .map_or(Vec3::ZERO, |transform| transform.translation());
+        let half_width = PLAY_AREA.x / 2.0;
+
+        let offset = (position - listener).truncate();
+        let panning = 0.5 + 0.5 * STEREO_WIDTH * (offset.x / half_width).clamp(-1.0, 1.0);
+        let attenuation = HALF_VOLUME_DISTANCE / (HALF_VOLUME_DISTANCE + offset.length());
+
+        // The volume of a single sound replaces the one of the channel, so apply the settings too.
+        self.channel
+            .play(source)
+            .with_panning(panning as f64)
+            .with_volume(self.settings.volume(Sound::Sfx) * attenuation as f64);
+    }
+}
 
 pub struct AudioPlugin;
 
 impl Plugin for AudioPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_set(SystemSet::on_enter(GameState::MainMenu).with_system(menu_music))
+        app.add_audio_channel::<MusicChannel>()
+            .add_audio_channel::<SfxChannel>()
+            .add_audio_channel::<VoiceChannel>()
+            .init_resource::<Music>()
+            // Before the music of a new state starts, so it starts at the right volume.
+            .add_system_to_stage(CoreStage::PreUpdate, update_volume)
+            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(intensify_music))
+            .add_system_set(SystemSet::on_enter(GameState::MainMenu).with_system(menu_music))
             .add_system_set(SystemSet::on_exit(GameState::MainMenu).with_system(stop))
             .add_system_set(SystemSet::on_enter(GameState::Intro).with_system(intro_music))
             .add_system_set(SystemSet::on_exit(GameState::Intro).with_system(stop))
@@ -23,66 +167,163 @@ impl Plugin for AudioPlugin {
     }
 }
 
-fn menu_music(asset_server: Res<AssetServer>, audio: Res<Audio>) {
-    audio
-        .play(asset_server.load("sounds/emotinialpath.wav"))
-        .with_volume(0.5)
-        .looped();
+fn menu_music(
+    asset_server: Res<AssetServer>,
+    channel: Res<AudioChannel<MusicChannel>>,
+    mut music: ResMut<Music>,
+) {
+    music.play(
+        &channel,
+        asset_server.load("sounds/emotinialpath.wav"),
+        None,
+    );
 }
 
-fn intro_music(asset_server: Res<AssetServer>, audio: Res<Audio>) {
-    audio
-        .play(asset_server.load("sounds/condensOnACoffeeMuck.wav"))
-        .with_volume(0.5)
-        .looped();
+fn intro_music(
+    asset_server: Res<AssetServer>,
+    channel: Res<AudioChannel<MusicChannel>>,
+    mut music: ResMut<Music>,
+) {
+    music.play(
+        &channel,
+        asset_server.load("sounds/condensOnACoffeeMuck.wav"),
+        None,
+    );
 }
 
-fn play_music(asset_server: Res<AssetServer>, audio: Res<Audio>, level: Res<Level>) {
-    audio
-        .play(asset_server.load(match level.level {
+/// The more energetic version of the track of a floor, if it has one.
+fn intense_track(level: &Level) -> Option<&'static str> {
+    match level.level {
+        1 => Some("sounds/emotinailpathOSCBreakout.wav"),
+        2 => Some("sounds/condensOnACoffeeMuckpiano.wav"),
+        // The tracks of these floors have no variant as long as they are.
+        3 | 4 => None,
+        _ => Some("sounds/emotinailpathOSCBreakout.wav"),
+    }
+}
+
+fn play_music(
+    asset_server: Res<AssetServer>,
+    channel: Res<AudioChannel<MusicChannel>>,
+    mut music: ResMut<Music>,
+    level: Res<Level>,
+) {
+    music.play(
+        &channel,
+        asset_server.load(match level.level {
             1 => "sounds/emotinialpathWithOsc.wav",
             2 => "sounds/condensOnACoffeeMuck.wav",
             3 => "sounds/ok_agan.wav",
             4 => "sounds/all_hands_on_deck.wav",
             _ => "sounds/emotinialpathWithOsc.wav",
-        }))
-        .with_volume(0.5)
-        .looped();
+        }),
+        intense_track(&level).map(|path| asset_server.load(path)),
+    );
 }
 
-fn win_music(asset_server: Res<AssetServer>, audio: Res<Audio>, level: Res<Level>) {
-    audio
-        .play(asset_server.load(match level.level {
+fn win_music(
+    asset_server: Res<AssetServer>,
+    channel: Res<AudioChannel<MusicChannel>>,
+    mut music: ResMut<Music>,
+    level: Res<Level>,
+) {
+    music.play(
+        &channel,
+        asset_server.load(match level.level {
             1 => "sounds/emotinailpathOSCBreakout.wav",
             2 => "sounds/condensOnACoffeeMuckpiano.wav",
             3 => "sounds/ok_agan_win.wav",
             4 => "sounds/all_hands_on_deck_win.wav",
             _ => "sounds/emotinailpathOSCBreakout.wav",
-        }))
-        .with_volume(0.5)
-        .looped();
+        }),
+        None,
+    );
 }
 
-fn lose_music(asset_server: Res<AssetServer>, audio: Res<Audio>, level: Res<Level>) {
-    audio
-        .play(asset_server.load(match level.level {
+fn lose_music(
+    asset_server: Res<AssetServer>,
+    channel: Res<AudioChannel<MusicChannel>>,
+    mut music: ResMut<Music>,
+    level: Res<Level>,
+) {
+    music.play(
+        &channel,
+        asset_server.load(match level.level {
             1 => "sounds/hide_paneel_lose.wav",
             2 => "sounds/condensOnACoffeeMuckLose.wav",
             3 => "sounds/ok_agan_lose.wav",
             4 => "sounds/all_hands_on_deck_lose.wav",
             _ => "sounds/hide_paneel_lose.wav",
-        }))
-        .with_volume(0.5)
-        .looped();
+        }),
+        None,
+    );
+}
+
+fn end_music(
+    asset_server: Res<AssetServer>,
+    channel: Res<AudioChannel<MusicChannel>>,
+    mut music: ResMut<Music>,
+) {
+    music.play(
+        &channel,
+        asset_server.load("sounds/emotinialpathWithOscUnfilterloop.wav"),
+        None,
+    );
 }
 
-fn end_music(asset_server: Res<AssetServer>, audio: Res<Audio>) {
-    audio
-        .play(asset_server.load("sounds/emotinialpathWithOscUnfilterloop.wav"))
-        .with_volume(0.5)
-        .looped();
+fn stop(mut music: ResMut<Music>, mut instances: ResMut<Assets<AudioInstance>>) {
+    music.fade_out(&mut instances);
 }
 
-fn stop(audio: Res<Audio>) {
-    audio.stop();
+fn update_volume(
+    settings: Res<Settings>,
+    mut music: ResMut<Music>,
+    mut instances: ResMut<Assets<AudioInstance>>,
+    voice: Res<AudioChannel<VoiceChannel>>,
+) {
+    if settings.is_changed() {
+        voice.set_volume(settings.volume(Sound::Voice));
+    }
+
+    let ducking = match voice.is_playing_sound() {
+        true => DUCKED_VOLUME,
+        false => 1.0,
+    };
+    let volume = settings.volume(Sound::Music) * ducking;
+
+    if volume != music.volume {
+        music.volume = volume;
+        music.apply_volume(&mut instances);
+    }
+}
+
+/// Mix in the intense variant of the track as more co-workers get close to the tower.
+fn intensify_music(
+    mut music: ResMut<Music>,
+    mut instances: ResMut<Assets<AudioInstance>>,
+    enemy_query: Query<(&Transform, &Moving), With<Enemy>>,
+    tower_query: Query<&Transform, With<Tower>>,
+    time: Res<Time>,
+) {
+    let nearby = enemy_query
+        .iter()
+        .filter(|(_, moving)| moving.state == MovingState::Normal)
+        .filter(|(transform, _)| {
+            tower_query
+                .iter()
+                .any(|tower| tower.translation.distance(transform.translation) < INTENSE_RADIUS)
+        })
+        .count();
+    let target = (nearby as f32 / INTENSE_COUNT).min(1.0) as f64;
+
+    let step = INTENSITY_RATE * time.delta_seconds_f64();
+    let intensity = music.intensity + (target - music.intensity).clamp(-step, step);
+
+    // Only send new volumes when the difference can be heard.
+    let audible = (intensity * 50.0).round() != (music.intensity * 50.0).round();
+    music.intensity = intensity;
+
+    if audible {
+        music.apply_volume(&mut instances);
+    }
 }
@@ -3,10 +3,24 @@
 //! The shaking algorithm is based on [an excellent presentation by Squirrel
 //! Eiserloh](https://www.youtube.com/watch?v=tu-Qe66AvtY).
 //!
+//! The game is laid out in a fixed logical play area of `PLAY_AREA` units, with the origin in its
+//! center. The camera scales it to fit the window, and the UI is scaled by the same amount, so all
+//! positions and sizes can be given in logical units.
+//!
+//! The UI is laid out inside a node covering the play area, which is surrounded by black bars to
+//! letterbox whatever else the camera sees. UI nodes without a parent are moved into it, so they
+//! are positioned relative to the play area rather than the window.
+//!
 
-use bevy::prelude::*;
+use bevy::{prelude::*, render::camera::ScalingMode};
 use rand::Rng;
 
+/// Size of the play area in logical units.
+pub const PLAY_AREA: Vec2 = Vec2::new(1280.0, 720.0);
+
+const MAX_ANGLE: f32 = 10.0;
+const MAX_OFFSET: f32 = 10.0;
+
 #[derive(Debug, Component)]
 pub struct CameraShake {
     pub trauma: f32,
@@ -18,26 +32,157 @@ impl Default for CameraShake {
     fn default() -> Self {
         CameraShake {
             trauma: 0.0,
-            max_angle: 10.0_f32.to_radians(),
-            max_offset: 10.0,
+            max_angle: MAX_ANGLE.to_radians(),
+            max_offset: MAX_OFFSET,
         }
     }
 }
 
+impl CameraShake {
+    /// Scale how far the camera moves when it shakes; 1.0 is the default.
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.max_angle = MAX_ANGLE.to_radians() * intensity;
+        self.max_offset = MAX_OFFSET * intensity;
+    }
+}
+
+/// UI node covering the play area, which holds all other UI.
+#[derive(Component)]
+pub struct PlayAreaNode;
+
+/// Root of the letterbox UI, which is left where it is.
+#[derive(Component)]
+struct Letterbox;
+
+/// Stage in which new UI is moved into the play area, after it has been spawned but before it is
+/// laid out.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, StageLabel)]
+struct LetterboxStage;
+
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
         app.add_startup_system(setup_camera)
+            .add_startup_system(setup_letterbox)
             .insert_resource(ClearColor(Color::rgb(0.0, 0.0, 0.0)))
-            .add_system(shake_camera);
+            .add_stage_before(
+                CoreStage::PostUpdate,
+                LetterboxStage,
+                SystemStage::single(adopt_ui),
+            )
+            .add_system(shake_camera)
+            .add_system(fit_to_window);
     }
 }
 
+/// How many logical pixels of the window a logical unit of the play area takes.
+fn play_area_scale(window: &Window) -> f32 {
+    (window.width() / PLAY_AREA.x).min(window.height() / PLAY_AREA.y)
+}
+
+/// Position of the cursor in the play area.
+pub fn cursor_position(window: &Window) -> Option<Vec2> {
+    let cursor = window.cursor_position()?;
+    let center = Vec2::new(window.width(), window.height()) / 2.0;
+    Some((cursor - center) / play_area_scale(window))
+}
+
 fn setup_camera(mut commands: Commands) {
-    commands.spawn((Camera2dBundle::default(), CameraShake::default()));
+    let mut camera = Camera2dBundle::default();
+    camera.projection.scaling_mode = ScalingMode::Auto {
+        min_width: PLAY_AREA.x,
+        min_height: PLAY_AREA.y,
+    };
+
+    commands.spawn((camera, CameraShake::default()));
+}
+
+/// Black bars around the play area, as large as the space the play area leaves in the window.
+fn setup_letterbox(mut commands: Commands) {
+    let bar = || NodeBundle {
+        style: Style {
+            flex_grow: 1.0,
+            ..default()
+        },
+        background_color: Color::BLACK.into(),
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ..default()
+            },
+            Letterbox,
+        ))
+        .with_children(|parent| {
+            parent.spawn(bar());
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(100.0), Val::Px(PLAY_AREA.y)),
+                        flex_shrink: 0.0,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(bar());
+                    parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Px(PLAY_AREA.x), Val::Px(PLAY_AREA.y)),
+                                flex_shrink: 0.0,
+                                overflow: Overflow::Hidden,
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        PlayAreaNode,
+                    ));
+                    parent.spawn(bar());
+                });
+            parent.spawn(bar());
+        });
+}
+
+/// Move UI which has just been spawned without a parent into the play area.
+fn adopt_ui(
+    mut commands: Commands,
+    play_area_query: Query<Entity, With<PlayAreaNode>>,
+    root_query: Query<Entity, (With<Node>, Without<Parent>, Without<Letterbox>)>,
+) {
+    let play_area = match play_area_query.get_single() {
+        Ok(play_area) => play_area,
+        Err(_) => return,
+    };
+
+    for entity in &root_query {
+        commands.entity(play_area).add_child(entity);
+    }
+}
+
+/// Scale the UI along with the play area, so it keeps covering the same part of it.
+fn fit_to_window(windows: Res<Windows>, mut ui_scale: ResMut<UiScale>) {
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+
+    let scale = play_area_scale(window);
+    if ui_scale.scale != scale as f64 {
+        ui_scale.scale = scale as f64;
+    }
 }
 
+#[allow(clippy::manual_clamp)]
 fn shake_camera(
     time: Res<Time>,
     mut camera_query: Query<(&mut CameraShake, &mut Transform), With<Camera>>,
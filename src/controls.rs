@@ -0,0 +1,144 @@
+//! Keyboard and gamepad controls, so the game can be played without a mouse.
+//!
+//! Number keys select items, the arrow keys, WASD, the d-pad or the left stick move the grid
+//! cursor, and items are placed with confirm and deselected with cancel.
+//!
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+/// Keys which select the item at the same position in the UI bar, or a choice in a dialogue.
+const ITEM_KEYS: [KeyCode; 9] = [
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+];
+
+/// How far the stick has to be pushed before the cursor moves.
+const STICK_THRESHOLD: f32 = 0.5;
+/// Time between steps of the cursor while the stick is held, in seconds.
+const STICK_REPEAT: f32 = 0.15;
+
+#[derive(SystemParam)]
+pub struct Controls<'w, 's> {
+    keys: Res<'w, Input<KeyCode>>,
+    gamepads: Res<'w, Gamepads>,
+    gamepad_buttons: Res<'w, Input<GamepadButton>>,
+    gamepad_axes: Res<'w, Axis<GamepadAxis>>,
+    time: Res<'w, Time>,
+    /// Time until the held stick moves the cursor again.
+    stick_cooldown: Local<'s, f32>,
+}
+
+impl<'w, 's> Controls<'w, 's> {
+    fn gamepad_just_pressed(&self, button_type: GamepadButtonType) -> bool {
+        self.gamepads.iter().any(|gamepad| {
+            self.gamepad_buttons
+                .just_pressed(GamepadButton::new(gamepad, button_type))
+        })
+    }
+
+    /// Index of the item the player selected with the number keys.
+    pub fn selected_item(&self) -> Option<usize> {
+        ITEM_KEYS
+            .iter()
+            .position(|key| self.keys.just_pressed(*key))
+    }
+
+    /// Whether to select the previous (-1) or next (1) item.
+    pub fn cycle_item(&self) -> i32 {
+        let previous = self.keys.just_pressed(KeyCode::Q)
+            || self.gamepad_just_pressed(GamepadButtonType::LeftTrigger);
+        let next = self.keys.just_pressed(KeyCode::E)
+            || self.gamepad_just_pressed(GamepadButtonType::RightTrigger);
+
+        next as i32 - previous as i32
+    }
+
+    pub fn confirm(&self) -> bool {
+        self.keys.just_pressed(KeyCode::Return)
+            || self.gamepad_just_pressed(GamepadButtonType::South)
+    }
+
+    pub fn cancel(&self) -> bool {
+        self.keys.just_pressed(KeyCode::Back) || self.gamepad_just_pressed(GamepadButtonType::East)
+    }
+
+    /// Direction to move the grid cursor in this frame, with the y-axis pointing up.
+    pub fn movement(&mut self) -> IVec2 {
+        let directions = [
+            (
+                KeyCode::Left,
+                KeyCode::A,
+                GamepadButtonType::DPadLeft,
+                IVec2::NEG_X,
+            ),
+            (
+                KeyCode::Right,
+                KeyCode::D,
+                GamepadButtonType::DPadRight,
+                IVec2::X,
+            ),
+            (
+                KeyCode::Down,
+                KeyCode::S,
+                GamepadButtonType::DPadDown,
+                IVec2::NEG_Y,
+            ),
+            (KeyCode::Up, KeyCode::W, GamepadButtonType::DPadUp, IVec2::Y),
+        ];
+
+        let mut movement = IVec2::ZERO;
+        for (arrow, key, button_type, direction) in directions {
+            if self.keys.just_pressed(arrow)
+                || self.keys.just_pressed(key)
+                || self.gamepad_just_pressed(button_type)
+            {
+                movement += direction;
+            }
+        }
+
+        movement + self.stick_movement()
+    }
+
+    fn stick_movement(&mut self) -> IVec2 {
+        let stick = self
+            .gamepads
+            .iter()
+            .map(|gamepad| {
+                let axis = |axis_type| {
+                    self.gamepad_axes
+                        .get(GamepadAxis::new(gamepad, axis_type))
+                        .unwrap_or(0.0)
+                };
+                Vec2::new(
+                    axis(GamepadAxisType::LeftStickX),
+                    axis(GamepadAxisType::LeftStickY),
+                )
+            })
+            .find(|stick| stick.length() > STICK_THRESHOLD);
+
+        let stick = match stick {
+            Some(stick) => stick,
+            None => {
+                *self.stick_cooldown = 0.0;
+                return IVec2::ZERO;
+            }
+        };
+
+        *self.stick_cooldown -= self.time.delta_seconds();
+        if *self.stick_cooldown > 0.0 {
+            return IVec2::ZERO;
+        }
+        *self.stick_cooldown = STICK_REPEAT;
+
+        // Snap the stick to the nearest of the eight directions.
+        let direction = stick.normalize().round();
+        IVec2::new(direction.x as i32, direction.y as i32)
+    }
+}
@@ -1,15 +1,39 @@
+//! Money to buy items with.
+//!
+//! Money comes from an income which can change over the course of a floor, a bounty for every
+//! co-worker who gives up, and interest on what is left unspent. How much of each is set by the
+//! economy of the floor (see `tuning`), and shown below the money.
+//!
+
 use bevy::prelude::*;
 
+use crate::enemies::EnemyLeftEvent;
 use crate::game::{GameState, Volatile};
+use crate::level::Level;
+use crate::localization::Localization;
+use crate::simulation::{simulation_step, SimulationPhase, SimulationStage, SimulationTime};
+use crate::tuning::Tuning;
 
 #[derive(Debug, Default, Resource)]
 pub struct Points {
     pub owned: f32,
+    /// Time since the start of the floor, in seconds.
+    elapsed: f32,
+    /// Time until the next payment of interest, in seconds.
+    until_interest: f32,
+    /// Money earned with bounties on this floor.
+    bounties: f32,
+    /// Money earned with interest on this floor.
+    interest: f32,
 }
 
 #[derive(Component)]
 struct PointsText;
 
+/// Where the money comes from, below the progress of the wave.
+#[derive(Component)]
+struct BreakdownText;
+
 pub struct CostPlugin;
 
 impl Plugin for CostPlugin {
@@ -20,29 +44,120 @@ impl Plugin for CostPlugin {
                     .with_system(setup_text)
                     .with_system(reset_points),
             )
-            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(increase_points));
+            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(pay_bounties))
+            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(update_text))
+            .add_system_set_to_stage(
+                SimulationStage,
+                simulation_step(SimulationPhase::React)
+                    .with_system(increase_points)
+                    .with_system(pay_interest.after(increase_points)),
+            );
     }
 }
 
 fn increase_points(
     mut points: ResMut<Points>,
+    tuning: Res<Tuning>,
+    level: Res<Level>,
+    simulation_time: Res<SimulationTime>,
+) {
+    let economy = tuning.economy(&level);
+    let delta = simulation_time.delta_seconds();
+
+    points.owned += economy.income_rate(points.elapsed) * delta;
+    points.elapsed += delta;
+}
+
+fn pay_interest(
+    mut points: ResMut<Points>,
+    tuning: Res<Tuning>,
+    level: Res<Level>,
+    simulation_time: Res<SimulationTime>,
+) {
+    let economy = tuning.economy(&level);
+    if economy.interest_interval <= 0.0 {
+        return;
+    }
+
+    points.until_interest -= simulation_time.delta_seconds();
+    while points.until_interest <= 0.0 {
+        points.until_interest += economy.interest_interval;
+
+        let interest = points.owned.max(0.0) * economy.interest;
+        points.owned += interest;
+        points.interest += interest;
+    }
+}
+
+fn pay_bounties(
+    mut ev_enemy_left: EventReader<EnemyLeftEvent>,
+    mut points: ResMut<Points>,
+    tuning: Res<Tuning>,
+    level: Res<Level>,
+) {
+    let bounty = tuning.economy(&level).bounty;
+
+    for _ in ev_enemy_left.iter() {
+        points.owned += bounty;
+        points.bounties += bounty;
+    }
+}
+
+fn update_text(
+    points: Res<Points>,
+    tuning: Res<Tuning>,
+    level: Res<Level>,
+    localization: Res<Localization>,
     mut text_query: Query<&mut Text, With<PointsText>>,
-    time: Res<Time>,
+    mut breakdown_query: Query<&mut Text, (With<BreakdownText>, Without<PointsText>)>,
 ) {
+    let economy = tuning.economy(&level);
     let mut text = text_query.single_mut();
+    text.sections[0].value = localization.format("points", &[("points", &(points.owned as u32))]);
 
-    points.owned += time.delta_seconds() * 5.0;
-    text.sections[0].value = format!("${}", points.owned as u32);
+    // Only list what the floor pays out.
+    let mut breakdown = vec![localization.format(
+        "points.income",
+        &[(
+            "rate",
+            &format!("{:.1}", economy.income_rate(points.elapsed)),
+        )],
+    )];
+    if economy.bounty > 0.0 {
+        breakdown.push(localization.format(
+            "points.bounties",
+            &[
+                ("bounty", &economy.bounty),
+                ("earned", &(points.bounties as u32)),
+            ],
+        ));
+    }
+    if economy.interest > 0.0 {
+        breakdown.push(localization.format(
+            "points.interest",
+            &[
+                ("percentage", &((economy.interest * 1000.0).round() / 10.0)),
+                ("earned", &(points.interest as u32)),
+            ],
+        ));
+    }
+    breakdown_query.single_mut().sections[0].value = breakdown.join("\n");
 }
 
-fn reset_points(mut points: ResMut<Points>) {
-    points.owned = 30.0;
+fn reset_points(mut points: ResMut<Points>, tuning: Res<Tuning>, level: Res<Level>) {
+    let economy = tuning.economy(&level);
+
+    *points = Points {
+        owned: economy.starting_money,
+        until_interest: economy.interest_interval,
+        ..default()
+    };
 }
 
 fn setup_text(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.spawn((
         TextBundle::from_section(
-            "$123",
+            "",
             TextStyle {
                 font: asset_server.load("fonts/PixeloidSans.ttf"),
                 font_size: 40.0,
@@ -62,4 +177,27 @@ fn setup_text(mut commands: Commands, asset_server: Res<AssetServer>) {
         PointsText,
         Volatile,
     ));
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: asset_server.load("fonts/PixeloidSans.ttf"),
+                font_size: 16.0,
+                color: Color::rgba(0.4, 0.9, 0.4, 0.8),
+            },
+        )
+        .with_text_alignment(TextAlignment::TOP_RIGHT)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                top: Val::Px(80.0),
+                right: Val::Px(5.0),
+                ..default()
+            },
+            ..default()
+        }),
+        BreakdownText,
+        Volatile,
+    ));
 }
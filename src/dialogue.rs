@@ -0,0 +1,743 @@
+//! Dialogue scripts and a runner to play them.
+//!
+//! A dialogue script is a RON file in `assets/dialogues` with the `.dialogue.ron` extension. It
+//! defines the speakers of a conversation and the lines they say, for example:
+//!
+//! ```ron
+//! (
+//!     speakers: {
+//!         "bob": (color: (1.0, 0.0, 1.0)),
+//!     },
+//!     lines: [
+//!         (speaker: "bob", voice: Some("sounds/Bob_Intro1.mp3"), text: "intro.2"),
+//!         (
+//!             speaker: "bob",
+//!             text: "bob.again",
+//!             choices: [(text: "yes", goto: Some("start")), (text: "no")],
+//!         ),
+//!     ],
+//! )
+//! ```
+//!
+//! The text of lines and choices are keys into the string tables of the `localization` module.
+//! Lines are played in order, unless a line has a `goto` to the `label` of another line. A line
+//! with `choices` waits until the player picks one, and continues at the `goto` of that choice.
+//!
+//! Send a `StartDialogueEvent` to play a script in any state; the runner can switch to another
+//! state once the script has finished.
+//!
+//! The text of a line is typed out in step with its voice, so it is complete when the voice has
+//! finished. Clicking completes the line, or continues once it is complete. Escape skips the rest
+//! of the script, and Tab opens a back-log of the lines played so far, which can be scrolled with
+//! the mouse wheel.
+//!
+
+use bevy::{
+    asset::{AssetLoader, BoxedFuture, Error, LoadContext, LoadedAsset},
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::{HashMap, HashSet},
+};
+use bevy_kira_audio::prelude::*;
+use serde::Deserialize;
+
+use crate::audio::VoiceChannel;
+use crate::controls::Controls;
+use crate::game::GameState;
+use crate::localization::Localization;
+
+/// How fast the text of a line without a voice is typed out.
+const CHARACTERS_PER_SECOND: f32 = 30.0;
+/// Pixels to scroll the back-log per line of the mouse wheel.
+const SCROLL_LINE_HEIGHT: f32 = 20.0;
+
+#[derive(Debug, Deserialize)]
+pub struct Speaker {
+    /// RGB color of the lines of this speaker.
+    pub color: (f32, f32, f32),
+    /// Image shown next to the lines of this speaker.
+    #[serde(default)]
+    pub portrait: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Choice {
+    pub text: String,
+    /// Label of the line to continue with, or the next line if not set.
+    #[serde(default)]
+    pub goto: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Line {
+    pub speaker: String,
+    pub text: String,
+    #[serde(default)]
+    pub voice: Option<String>,
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Label of the line to continue with, or the next line if not set.
+    #[serde(default)]
+    pub goto: Option<String>,
+    #[serde(default)]
+    pub choices: Vec<Choice>,
+}
+
+impl Line {
+    fn characters(&self, localization: &Localization) -> f32 {
+        localization.get(&self.text).chars().count() as f32
+    }
+}
+
+#[derive(Debug, Deserialize, TypeUuid)]
+#[uuid = "452ac1c2-7615-4cbe-854f-1d330001f1d2"]
+pub struct DialogueScript {
+    #[serde(default)]
+    pub speakers: HashMap<String, Speaker>,
+    pub lines: Vec<Line>,
+}
+
+impl DialogueScript {
+    /// Find the index of the line to continue with after a `goto`.
+    fn next_line(&self, current: usize, goto: &Option<String>) -> usize {
+        match goto {
+            Some(label) => self
+                .lines
+                .iter()
+                .position(|line| line.label.as_ref() == Some(label))
+                .unwrap_or_else(|| {
+                    warn!("Unknown dialogue label: {}", label);
+                    self.lines.len()
+                }),
+            None => current + 1,
+        }
+    }
+}
+
+#[derive(Default)]
+struct DialogueLoader;
+
+impl AssetLoader for DialogueLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let script: DialogueScript = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(script));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["dialogue.ron"]
+    }
+}
+
+pub struct StartDialogueEvent {
+    pub path: String,
+    /// State to switch to once the dialogue has finished.
+    pub then: Option<GameState>,
+}
+
+#[derive(Debug, Default, Resource)]
+pub struct DialogueRunner {
+    script: Option<Handle<DialogueScript>>,
+    path: String,
+    line: usize,
+    played_sound: bool,
+    /// Number of characters of the current line which are shown.
+    revealed: f32,
+    voice: Option<Handle<AudioInstance>>,
+    /// Voice clip of the current line, to know how long it takes.
+    clip: Option<Handle<AudioSource>>,
+    then: Option<GameState>,
+    /// Translated lines played so far in this script, with the color of their speaker.
+    back_log: Vec<(Color, String)>,
+    /// Scripts which have been played until the end since the main menu.
+    seen: HashSet<String>,
+}
+
+impl DialogueRunner {
+    pub fn is_active(&self) -> bool {
+        self.script.is_some()
+    }
+
+    pub fn has_seen(&self, path: &str) -> bool {
+        self.seen.contains(path)
+    }
+
+    fn go_to(&mut self, line: usize) {
+        self.line = line;
+        self.played_sound = false;
+        self.revealed = 0.0;
+    }
+
+    /// Close the text view and switch to the next state.
+    fn finish(
+        &mut self,
+        commands: &mut Commands,
+        game_state: &mut State<GameState>,
+        textview_query: &Query<Entity, With<TextView>>,
+    ) {
+        for entity in textview_query {
+            commands.entity(entity).despawn_recursive();
+        }
+
+        let path = self.path.clone();
+        self.seen.insert(path);
+        self.script = None;
+
+        if let Some(state) = self.then.take() {
+            game_state.set(state).unwrap();
+        }
+    }
+}
+
+#[derive(Component)]
+struct TextView;
+
+#[derive(Component)]
+struct Conversation;
+
+#[derive(Component)]
+struct Portrait;
+
+#[derive(Component)]
+struct ChoiceList;
+
+#[derive(Component)]
+struct ChoiceButton {
+    goto: Option<String>,
+}
+
+#[derive(Component)]
+struct BackLog;
+
+#[derive(Component, Default)]
+struct BackLogList {
+    position: f32,
+}
+
+pub struct DialoguePlugin;
+
+impl Plugin for DialoguePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<DialogueScript>()
+            .init_asset_loader::<DialogueLoader>()
+            .insert_resource(DialogueRunner::default())
+            .add_event::<StartDialogueEvent>()
+            .add_system_set(SystemSet::on_enter(GameState::MainMenu).with_system(reset_seen))
+            .add_system(start_dialogue)
+            .add_system(update_dialogue.after(start_dialogue))
+            .add_system(next_line.after(update_dialogue))
+            .add_system(click_choice.after(next_line))
+            .add_system(skip_dialogue.after(click_choice))
+            .add_system(toggle_back_log.after(skip_dialogue))
+            .add_system(scroll_back_log);
+    }
+}
+
+fn reset_seen(mut runner: ResMut<DialogueRunner>) {
+    runner.seen.clear();
+}
+
+fn start_dialogue(
+    mut commands: Commands,
+    mut ev_start_dialogue: EventReader<StartDialogueEvent>,
+    mut runner: ResMut<DialogueRunner>,
+    textview_query: Query<Entity, With<TextView>>,
+    asset_server: Res<AssetServer>,
+) {
+    for ev in ev_start_dialogue.iter() {
+        runner.script = Some(asset_server.load(&ev.path));
+        runner.path = ev.path.clone();
+        runner.then = ev.then.clone();
+        runner.back_log.clear();
+        runner.go_to(0);
+
+        for entity in &textview_query {
+            commands.entity(entity).despawn_recursive();
+        }
+
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(100.0), Val::Px(200.0)),
+                        border: UiRect::all(Val::Px(2.0)),
+                        position: UiRect {
+                            bottom: Val::Px(0.0),
+                            ..default()
+                        },
+                        position_type: PositionType::Absolute,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.1, 0.1, 0.1).into(),
+                    ..default()
+                },
+                TextView,
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    ImageBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(160.0), Val::Px(160.0)),
+                            margin: UiRect::all(Val::Px(20.0)),
+                            display: Display::None,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    Portrait,
+                ));
+
+                parent.spawn((
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font: asset_server.load("fonts/PixeloidSans.ttf"),
+                            font_size: 50.0,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    Conversation,
+                ));
+
+                parent.spawn((
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            margin: UiRect::left(Val::Px(40.0)),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    ChoiceList,
+                ));
+            });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_dialogue(
+    mut commands: Commands,
+    mut runner: ResMut<DialogueRunner>,
+    scripts: Res<Assets<DialogueScript>>,
+    instances: Res<Assets<AudioInstance>>,
+    sources: Res<Assets<AudioSource>>,
+    mut text_query: Query<&mut Text, With<Conversation>>,
+    mut portrait_query: Query<(&mut UiImage, &mut Style), With<Portrait>>,
+    mut choice_list_query: Query<
+        (Entity, &mut Style, Option<&Children>),
+        (With<ChoiceList>, Without<Portrait>),
+    >,
+    asset_server: Res<AssetServer>,
+    localization: Res<Localization>,
+    voice_channel: Res<AudioChannel<VoiceChannel>>,
+    time: Res<Time>,
+) {
+    let script = match runner
+        .script
+        .as_ref()
+        .and_then(|handle| scripts.get(handle))
+    {
+        Some(script) => script,
+        None => return,
+    };
+
+    let line = match script.lines.get(runner.line) {
+        Some(line) => line,
+        None => return,
+    };
+
+    let speaker = script.speakers.get(&line.speaker);
+    let (r, g, b) = speaker.map_or((1.0, 1.0, 1.0), |speaker| speaker.color);
+    let color = Color::rgb(r, g, b);
+    let line_text = localization.get(&line.text);
+    let characters = line.characters(&localization);
+
+    if !runner.played_sound {
+        // This only has to happen once, when a new line starts.
+        runner.clip = line
+            .voice
+            .as_ref()
+            .map(|file| asset_server.load(file.as_str()));
+        runner.voice = runner
+            .clip
+            .clone()
+            .map(|clip| voice_channel.play(clip).handle());
+        runner.back_log.push((color, line_text.to_string()));
+
+        if let Ok((mut image, mut style)) = portrait_query.get_single_mut() {
+            match speaker.and_then(|speaker| speaker.portrait.as_ref()) {
+                Some(portrait) => {
+                    *image = asset_server.load(portrait.as_str()).into();
+                    style.display = Display::Flex;
+                }
+                None => style.display = Display::None,
+            }
+        }
+
+        if let Ok((choice_list, _, children)) = choice_list_query.get_single() {
+            for child in children.into_iter().flatten() {
+                commands.entity(*child).despawn_recursive();
+            }
+
+            commands.entity(choice_list).with_children(|parent| {
+                for choice in &line.choices {
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    padding: UiRect::all(Val::Px(5.0)),
+                                    margin: UiRect::all(Val::Px(5.0)),
+                                    ..default()
+                                },
+                                background_color: Color::rgb(0.2, 0.2, 0.2).into(),
+                                ..default()
+                            },
+                            ChoiceButton {
+                                goto: choice.goto.clone(),
+                            },
+                        ))
+                        .with_children(|button| {
+                            button.spawn(TextBundle::from_section(
+                                localization.get(&choice.text),
+                                TextStyle {
+                                    font: asset_server.load("fonts/PixeloidSans.ttf"),
+                                    font_size: 30.0,
+                                    color: Color::WHITE,
+                                },
+                            ));
+                        });
+                }
+            });
+        }
+
+        runner.played_sound = true;
+    }
+
+    // Reveal as much of the line as the voice has said, so both finish at the same time.
+    let state = runner
+        .voice
+        .as_ref()
+        .and_then(|handle| instances.get(handle))
+        .map(|instance| instance.state());
+    let duration = runner
+        .clip
+        .as_ref()
+        .and_then(|handle| sources.get(handle))
+        .map(|source| source.sound.duration().as_secs_f64());
+    let revealed = match (state, duration) {
+        _ if runner.voice.is_none() => {
+            runner.revealed + CHARACTERS_PER_SECOND * time.delta_seconds()
+        }
+        (Some(PlaybackState::Stopped), _) => characters,
+        (Some(state), Some(duration)) if duration > 0.0 => state
+            .position()
+            .map_or(0.0, |position| characters * (position / duration) as f32),
+        // The voice hasn't started yet.
+        _ => 0.0,
+    };
+    // Never hide text again, e.g. after the player completed the line.
+    runner.revealed = revealed.max(runner.revealed).min(characters);
+
+    if let Ok(mut text) = text_query.get_single_mut() {
+        text.sections[0].value = line_text.chars().take(runner.revealed as usize).collect();
+        text.sections[0].style.color = color;
+    }
+
+    // Only offer the choices once the whole question can be read.
+    if let Ok((_, mut style, _)) = choice_list_query.get_single_mut() {
+        style.display = if runner.revealed < characters {
+            Display::None
+        } else {
+            Display::Flex
+        };
+    }
+}
+
+/// Complete the current line on a click or confirm, or continue with the next one unless the
+/// player has to make a choice.
+#[allow(clippy::too_many_arguments)]
+fn next_line(
+    mut commands: Commands,
+    buttons: Res<Input<MouseButton>>,
+    controls: Controls,
+    mut runner: ResMut<DialogueRunner>,
+    scripts: Res<Assets<DialogueScript>>,
+    mut game_state: ResMut<State<GameState>>,
+    textview_query: Query<Entity, With<TextView>>,
+    back_log_query: Query<(), With<BackLog>>,
+    localization: Res<Localization>,
+) {
+    if !back_log_query.is_empty() {
+        return;
+    }
+
+    let script = match runner
+        .script
+        .as_ref()
+        .and_then(|handle| scripts.get(handle))
+    {
+        Some(script) => script,
+        None => return,
+    };
+
+    let next = match script.lines.get(runner.line) {
+        Some(_) if !buttons.just_pressed(MouseButton::Left) && !controls.confirm() => return,
+        Some(line) if runner.revealed < line.characters(&localization) => {
+            runner.revealed = line.characters(&localization);
+            return;
+        }
+        Some(line) if !line.choices.is_empty() => return,
+        Some(line) => script.next_line(runner.line, &line.goto),
+        None => runner.line,
+    };
+
+    if next < script.lines.len() {
+        runner.go_to(next);
+        return;
+    }
+
+    runner.finish(&mut commands, &mut game_state, &textview_query);
+}
+
+/// Make a choice by clicking it, or with the number key of its position.
+fn click_choice(
+    mut runner: ResMut<DialogueRunner>,
+    scripts: Res<Assets<DialogueScript>>,
+    interaction_query: Query<(&ChoiceButton, &Interaction), Changed<Interaction>>,
+    controls: Controls,
+    localization: Res<Localization>,
+) {
+    let script = match runner
+        .script
+        .as_ref()
+        .and_then(|handle| scripts.get(handle))
+    {
+        Some(script) => script,
+        None => return,
+    };
+
+    for (choice_button, interaction) in &interaction_query {
+        if *interaction == Interaction::Clicked {
+            let next = script.next_line(runner.line, &choice_button.goto);
+            runner.go_to(next);
+            return;
+        }
+    }
+
+    let choice = script.lines.get(runner.line).and_then(|line| {
+        let revealed = runner.revealed >= line.characters(&localization);
+        let index = controls.selected_item().filter(|_| revealed)?;
+        line.choices.get(index)
+    });
+    if let Some(choice) = choice {
+        let next = script.next_line(runner.line, &choice.goto);
+        runner.go_to(next);
+    }
+}
+
+/// Skip the rest of the script, e.g. to go straight to the game from the intro.
+fn skip_dialogue(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    mut runner: ResMut<DialogueRunner>,
+    mut instances: ResMut<Assets<AudioInstance>>,
+    mut game_state: ResMut<State<GameState>>,
+    textview_query: Query<Entity, With<TextView>>,
+    back_log_query: Query<(), With<BackLog>>,
+) {
+    // Escape closes the back-log first.
+    if !runner.is_active() || !back_log_query.is_empty() || !keys.just_pressed(KeyCode::Escape) {
+        return;
+    }
+
+    if let Some(instance) = runner
+        .voice
+        .take()
+        .and_then(|handle| instances.get_mut(&handle))
+    {
+        instance.stop(AudioTween::default());
+    }
+
+    runner.finish(&mut commands, &mut game_state, &textview_query);
+}
+
+fn toggle_back_log(
+    mut commands: Commands,
+    mut keys: ResMut<Input<KeyCode>>,
+    runner: Res<DialogueRunner>,
+    back_log_query: Query<Entity, With<BackLog>>,
+    asset_server: Res<AssetServer>,
+) {
+    if let Ok(back_log) = back_log_query.get_single() {
+        if !runner.is_active()
+            || keys.just_pressed(KeyCode::Tab)
+            || keys.just_pressed(KeyCode::Escape)
+        {
+            commands.entity(back_log).despawn_recursive();
+            keys.clear_just_pressed(KeyCode::Escape);
+        }
+        return;
+    }
+
+    if !runner.is_active() || !keys.just_pressed(KeyCode::Tab) {
+        return;
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(40.0)),
+                    overflow: Overflow::Hidden,
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.9).into(),
+                ..default()
+            },
+            BackLog,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            flex_grow: 1.0,
+                            max_size: Size::UNDEFINED,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    BackLogList::default(),
+                ))
+                .with_children(|parent| {
+                    // Newest lines first, so the player doesn't have to scroll for the last ones.
+                    for (color, text) in runner.back_log.iter().rev() {
+                        parent.spawn(
+                            TextBundle::from_section(
+                                text.clone(),
+                                TextStyle {
+                                    font: asset_server.load("fonts/PixeloidSans.ttf"),
+                                    font_size: 30.0,
+                                    color: *color,
+                                },
+                            )
+                            .with_style(Style {
+                                margin: UiRect::all(Val::Px(10.0)),
+                                ..default()
+                            }),
+                        );
+                    }
+                });
+        });
+}
+
+fn scroll_back_log(
+    mut ev_scroll: EventReader<MouseWheel>,
+    mut list_query: Query<(&mut BackLogList, &mut Style, &Parent, &Node)>,
+    node_query: Query<&Node>,
+) {
+    for ev in ev_scroll.iter() {
+        for (mut list, mut style, parent, list_node) in &mut list_query {
+            let items_height = list_node.size().y;
+            let panel_height = node_query.get(parent.get()).unwrap().size().y;
+            let max_scroll = (items_height - panel_height).max(0.0);
+
+            let dy = match ev.unit {
+                MouseScrollUnit::Line => ev.y * SCROLL_LINE_HEIGHT,
+                MouseScrollUnit::Pixel => ev.y,
+            };
+
+            list.position = (list.position + dy).clamp(-max_scroll, 0.0);
+            style.position.top = Val::Px(list.position);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIPT: &str = r#"(
+        speakers: {
+            "bob": (color: (1.0, 0.0, 1.0), portrait: Some("sprites/bob.png")),
+        },
+        lines: [
+            (
+                speaker: "bob",
+                label: Some("start"),
+                voice: Some("sounds/Bob_Intro1.mp3"),
+                text: "first",
+            ),
+            (speaker: "bob", text: "second", goto: Some("question")),
+            (speaker: "bob", text: "skipped"),
+            (
+                speaker: "bob",
+                label: Some("question"),
+                text: "question",
+                choices: [(text: "again", goto: Some("start")), (text: "on")],
+            ),
+            (speaker: "bob", text: "last"),
+        ],
+    )"#;
+
+    fn script() -> DialogueScript {
+        ron::de::from_str(SCRIPT).unwrap()
+    }
+
+    #[test]
+    fn parses_scripts() {
+        let script = script();
+
+        assert_eq!(script.lines.len(), 5);
+        assert_eq!(script.speakers["bob"].color, (1.0, 0.0, 1.0));
+        assert_eq!(
+            script.lines[0].voice.as_deref(),
+            Some("sounds/Bob_Intro1.mp3")
+        );
+        assert!(script.lines[2].label.is_none());
+        assert_eq!(script.lines[3].choices.len(), 2);
+    }
+
+    #[test]
+    fn plain_lines_continue_with_the_next_one() {
+        let script = script();
+
+        assert_eq!(script.next_line(0, &script.lines[0].goto), 1);
+    }
+
+    #[test]
+    fn goto_continues_at_the_label() {
+        let script = script();
+
+        assert_eq!(script.next_line(1, &script.lines[1].goto), 3);
+    }
+
+    #[test]
+    fn choices_continue_at_their_goto() {
+        let script = script();
+        let choices = &script.lines[3].choices;
+
+        assert_eq!(script.next_line(3, &choices[0].goto), 0);
+        assert_eq!(script.next_line(3, &choices[1].goto), 4);
+    }
+
+    #[test]
+    fn unknown_labels_end_the_script() {
+        let script = script();
+
+        assert_eq!(script.next_line(0, &Some("nowhere".to_string())), 5);
+    }
+}
@@ -0,0 +1,749 @@
+//! Level editor, to make floors without changing code.
+//!
+//! Press F2 in the main menu to open it. Furniture from `assets/sprites` is placed on the grid,
+//! together with the towers, the entrances and the co-workers who arrive through them. The floor
+//! can be test-played right away, and is exported to the level file (see `level`) it was opened
+//! from. The editor opens the level files of the floors of the game, and
+//! `assets/levels/custom.level.ron` for a new floor. Changes made to the open file while the
+//! editor is open are picked up right away.
+//!
+//! Tab switches between the tools. Clicking places with the current tool and right-clicking
+//! removes what is under the cursor. Q and E pick the furniture, entrance or co-worker to place.
+//! Furniture is flipped with F, resized with R and given another hit box with H. Co-workers are
+//! added at a time on the timeline, which is changed with the up and down arrows, and arrive
+//! through the entrance nearest to the click. L changes the lose condition, B whether placements
+//! may block every route to the elephant, O opens the next level file, P test-plays, F5 exports
+//! and Escape returns to the main menu. Escape also stops a test-play.
+//!
+
+use bevy::{asset::LoadState, prelude::*};
+
+use crate::camera::{cursor_position, PLAY_AREA};
+use crate::force::ForceType;
+use crate::game::{GameState, Volatile};
+use crate::grid::{get_coordinates, get_indeces, snap, CELL_SIZE};
+use crate::level::{
+    setup_floor, Arrival, Entrance, EntranceKind, Level, LevelLayout, LoseCondition, Wall,
+    FLOOR_FILES,
+};
+use crate::localization::Localization;
+use crate::physics::Collider;
+use crate::settings::SettingsMenu;
+
+/// Level file for a floor which isn't part of the game, opened after those of the floors.
+const CUSTOM_FILE: &str = "levels/custom.level.ron";
+
+/// Furniture which can be placed.
+const CATALOGUE: [&str; 23] = [
+    "sprites/archive_cabinet.png",
+    "sprites/Cabinet.png",
+    "sprites/desk_A.png",
+    "sprites/desk_B.png",
+    "sprites/desk_C.png",
+    "sprites/desk_D.png",
+    "sprites/conference_desk.png",
+    "sprites/office_chair.png",
+    "sprites/office_chair_back.png",
+    "sprites/Cubicle_screen.png",
+    "sprites/Cubicle_screenB.png",
+    "sprites/Cubicle_screen_corner_A.png",
+    "sprites/Cubicle_screen_corner_B.png",
+    "sprites/Cubicle_screen_side.png",
+    "sprites/Cubicle_screen_square_A.png",
+    "sprites/Cubicle_screen_square_B.png",
+    "sprites/Cubicle_side.png",
+    "sprites/plant_A.png",
+    "sprites/plant_B.png",
+    "sprites/boxA.png",
+    "sprites/boxB.png",
+    "sprites/Stack_of_work.png",
+    "sprites/Stack_of_work_B.png",
+];
+
+/// Sizes furniture can have, relative to the other furniture.
+const SCALES: [f32; 4] = [1.0, 1.1, 1.5, 2.0];
+
+/// Hit boxes furniture can have. Without one, it blocks the cell it stands on.
+const HIT_BOXES: [Option<Vec2>; 5] = [
+    None,
+    Some(Vec2::new(2.0 * CELL_SIZE, CELL_SIZE)),
+    Some(Vec2::new(CELL_SIZE, 2.0 * CELL_SIZE)),
+    Some(Vec2::new(3.0 * CELL_SIZE, CELL_SIZE)),
+    Some(Vec2::new(5.0 * CELL_SIZE, 0.75 * CELL_SIZE)),
+];
+
+/// Co-workers who can arrive: their spritesheet, the size of a frame and how they behave.
+const COWORKERS: [(&str, Vec2, ForceType); 4] = [
+    (
+        "sprites/spritesheet_NPC01_M_walk.png",
+        Vec2::new(16.0, 24.0),
+        ForceType::Passive,
+    ),
+    (
+        "sprites/spritesheet_NPC02_M_walk.png",
+        Vec2::new(16.0, 24.0),
+        ForceType::Passive,
+    ),
+    (
+        "sprites/spritesheet_NPC03_M_walk.png",
+        Vec2::new(16.0, 24.0),
+        ForceType::Passive,
+    ),
+    (
+        "sprites/spritesheet_NPC04_M_walk.png",
+        Vec2::new(16.0, 32.0),
+        ForceType::Repel,
+    ),
+];
+
+/// Seconds the time on the timeline changes per key press.
+const TIMELINE_STEP: f32 = 1.0;
+
+const HIT_BOX_COLOR: Color = Color::rgba(1.0, 0.3, 0.3, 0.3);
+const CURSOR_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.3);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Tool {
+    #[default]
+    Furniture,
+    Tower,
+    Entrance,
+    Arrival,
+}
+
+impl Tool {
+    const ALL: [Tool; 4] = [Tool::Furniture, Tool::Tower, Tool::Entrance, Tool::Arrival];
+
+    /// Localization key of the name.
+    fn name(&self) -> &'static str {
+        match self {
+            Tool::Furniture => "editor.tool.furniture",
+            Tool::Tower => "editor.tool.tower",
+            Tool::Entrance => "editor.tool.entrance",
+            Tool::Arrival => "editor.tool.arrival",
+        }
+    }
+
+    /// Amount of things the tool can place.
+    fn items(&self) -> usize {
+        match self {
+            Tool::Furniture => CATALOGUE.len(),
+            Tool::Tower => 1,
+            Tool::Entrance => EntranceKind::ALL.len(),
+            Tool::Arrival => COWORKERS.len(),
+        }
+    }
+}
+
+#[derive(Default, Resource)]
+struct Editor {
+    layout: LevelLayout,
+    /// Index of the open level file, see `Editor::path`.
+    opened: usize,
+    /// Open level file, which is kept loaded so changes to it are picked up.
+    file: Option<Handle<LevelLayout>>,
+    /// Whether the layout still has to be taken from the level file once it has been read.
+    loading: bool,
+    tool: Tool,
+    /// Index of the thing to place with the tool.
+    item: usize,
+    /// Index into `SCALES` of the furniture to place.
+    scale: usize,
+    flipped: bool,
+    /// Index into `HIT_BOXES` of the furniture to place.
+    hit_box: usize,
+    /// Time on the timeline at which co-workers are added, in seconds.
+    seconds: f32,
+    /// Localization key of the outcome of the last action.
+    message: Option<&'static str>,
+}
+
+impl Editor {
+    /// Path of the open level file, within the assets.
+    fn path(&self) -> &'static str {
+        FLOOR_FILES.get(self.opened).unwrap_or(&CUSTOM_FILE)
+    }
+
+    /// Floor of the game the open level file is of, or zero for the custom one.
+    fn floor(&self) -> u8 {
+        match self.opened < FLOOR_FILES.len() {
+            true => self.opened as u8 + 1,
+            false => 0,
+        }
+    }
+
+    /// Open a level file, and start with its layout once it has been read.
+    fn open(&mut self, opened: usize, asset_server: &AssetServer) {
+        self.opened = opened;
+        self.file = Some(asset_server.load(self.path()));
+        self.loading = true;
+        self.layout = LevelLayout::default();
+    }
+
+    fn wall(&self, row: usize, column: usize) -> Wall {
+        Wall {
+            sprite: CATALOGUE[self.item].to_string(),
+            row,
+            column,
+            scale: SCALES[self.scale],
+            flipped: self.flipped,
+            hit_box: HIT_BOXES[self.hit_box],
+        }
+    }
+
+    /// Index of the entrance nearest to a position.
+    fn nearest_entrance(&self, position: Vec2) -> Option<usize> {
+        self.layout
+            .entrances
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                a.position
+                    .distance(position)
+                    .total_cmp(&b.position.distance(position))
+            })
+            .map(|(index, _)| index)
+    }
+
+    fn place(&mut self, position: Vec2) {
+        match self.tool {
+            Tool::Furniture => {
+                let (row, column) = get_indeces(position);
+                let wall = self.wall(row, column);
+                self.layout.walls.push(wall);
+            }
+            Tool::Tower => {
+                let position = snap(position);
+                if !self.layout.towers.contains(&position) {
+                    self.layout.towers.push(position);
+                }
+            }
+            Tool::Entrance => {
+                let entrance = Entrance::at_edge(EntranceKind::ALL[self.item], snap(position));
+                self.layout.entrances.push(entrance);
+            }
+            Tool::Arrival => match self.nearest_entrance(position) {
+                Some(entrance) => {
+                    let (sprite, sprite_size, force_type) = COWORKERS[self.item];
+                    self.layout.arrivals.push(Arrival {
+                        seconds: self.seconds,
+                        sprite: sprite.to_string(),
+                        sprite_size,
+                        force_type,
+                        entrance,
+                    });
+                    self.layout
+                        .arrivals
+                        .sort_by(|a, b| a.seconds.total_cmp(&b.seconds));
+                }
+                None => self.message = Some("editor.no_entrance"),
+            },
+        }
+    }
+
+    fn remove(&mut self, position: Vec2) {
+        match self.tool {
+            Tool::Furniture => {
+                let cell = get_indeces(position);
+                let walls = &mut self.layout.walls;
+                if let Some(index) = walls
+                    .iter()
+                    .rposition(|wall| (wall.row, wall.column) == cell)
+                {
+                    walls.remove(index);
+                }
+            }
+            Tool::Tower => {
+                let position = snap(position);
+                self.layout.towers.retain(|tower| *tower != position);
+            }
+            Tool::Entrance => {
+                if let Some(removed) = self.nearest_entrance(position) {
+                    self.layout.entrances.remove(removed);
+                    // Co-workers keep arriving through the same entrances.
+                    for arrival in &mut self.layout.arrivals {
+                        if arrival.entrance > removed {
+                            arrival.entrance -= 1;
+                        } else if arrival.entrance == removed {
+                            arrival.entrance = 0;
+                        }
+                    }
+                }
+            }
+            Tool::Arrival => {
+                let seconds = self.seconds;
+                let arrivals = &mut self.layout.arrivals;
+                if let Some(index) = arrivals
+                    .iter()
+                    .rposition(|arrival| arrival.seconds == seconds)
+                {
+                    arrivals.remove(index);
+                }
+            }
+        }
+    }
+}
+
+/// Everything the editor shows, which is replaced whenever the layout changes.
+#[derive(Component)]
+struct EditorItem;
+
+/// Preview of what is placed at the cursor.
+#[derive(Component)]
+struct EditorCursor;
+
+#[derive(Component)]
+struct EditorText;
+
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Editor>()
+            .add_system_set(SystemSet::on_update(GameState::MainMenu).with_system(open_editor))
+            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(stop_test_play))
+            .add_system_set(
+                SystemSet::on_enter(GameState::Editor)
+                    .with_system(cleanup_editor)
+                    .with_system(setup_floor)
+                    .with_system(setup_editor),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Editor)
+                    .with_system(load_layout)
+                    .with_system(use_keys)
+                    .with_system(use_mouse)
+                    .with_system(show_layout.after(use_keys).after(use_mouse))
+                    .with_system(move_cursor.after(show_layout)),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Editor).with_system(cleanup_editor));
+    }
+}
+
+/// Open the editor on F2, unless the settings screen is open.
+fn open_editor(
+    keys: Res<Input<KeyCode>>,
+    settings_query: Query<(), With<SettingsMenu>>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    if keys.just_pressed(KeyCode::F2) && settings_query.is_empty() {
+        // Fails when the menu has started the game this frame already.
+        let _ = game_state.set(GameState::Editor);
+    }
+}
+
+fn stop_test_play(
+    keys: Res<Input<KeyCode>>,
+    level: Res<Level>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    if level.test_play && keys.just_pressed(KeyCode::Escape) {
+        // Fails when the floor has been won or lost this frame already, which returns to the
+        // editor as well.
+        let _ = game_state.set(GameState::Editor);
+    }
+}
+
+fn setup_editor(
+    mut commands: Commands,
+    mut editor: ResMut<Editor>,
+    mut level: ResMut<Level>,
+    asset_server: Res<AssetServer>,
+) {
+    if level.test_play {
+        // Back from a test-play.
+        editor.layout = std::mem::take(&mut level.layout);
+        level.test_play = false;
+    } else {
+        // Open the level file which was open the last time.
+        let opened = editor.opened;
+        *editor = Editor::default();
+        editor.open(opened, &asset_server);
+    }
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: asset_server.load("fonts/PixeloidSans.ttf"),
+                font_size: 20.0,
+                color: Color::WHITE,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                top: Val::Px(5.0),
+                left: Val::Px(5.0),
+                ..default()
+            },
+            ..default()
+        }),
+        EditorText,
+        Volatile,
+    ));
+}
+
+/// Start with the level file once it has been read, if there is one, and take it again whenever
+/// it changes on disk.
+fn load_layout(
+    mut editor: ResMut<Editor>,
+    mut ev_asset: EventReader<AssetEvent<LevelLayout>>,
+    layouts: Res<Assets<LevelLayout>>,
+    asset_server: Res<AssetServer>,
+) {
+    let handle = match editor.file.clone() {
+        Some(handle) => handle,
+        None => return,
+    };
+
+    let modified = ev_asset
+        .iter()
+        .any(|ev| matches!(ev, AssetEvent::Modified { handle: changed } if *changed == handle));
+    if !editor.loading && !modified {
+        return;
+    }
+
+    if let Some(layout) = layouts.get(&handle) {
+        editor.layout = layout.clone();
+        editor.loading = false;
+    } else if asset_server.get_load_state(&handle) == LoadState::Failed {
+        editor.loading = false;
+    }
+}
+
+fn use_keys(
+    keys: Res<Input<KeyCode>>,
+    mut editor: ResMut<Editor>,
+    mut level: ResMut<Level>,
+    mut game_state: ResMut<State<GameState>>,
+    asset_server: Res<AssetServer>,
+) {
+    if keys.just_pressed(KeyCode::Tab) {
+        let index = Tool::ALL.iter().position(|tool| *tool == editor.tool);
+        editor.tool = Tool::ALL[index.map_or(0, |index| (index + 1) % Tool::ALL.len())];
+        editor.item = 0;
+    }
+
+    let items = editor.tool.items();
+    if keys.just_pressed(KeyCode::Q) {
+        editor.item = (editor.item + items - 1) % items;
+    }
+    if keys.just_pressed(KeyCode::E) {
+        editor.item = (editor.item + 1) % items;
+    }
+
+    if keys.just_pressed(KeyCode::F) {
+        editor.flipped = !editor.flipped;
+    }
+    if keys.just_pressed(KeyCode::R) {
+        editor.scale = (editor.scale + 1) % SCALES.len();
+    }
+    if keys.just_pressed(KeyCode::H) {
+        editor.hit_box = (editor.hit_box + 1) % HIT_BOXES.len();
+    }
+
+    if keys.just_pressed(KeyCode::Up) {
+        editor.seconds += TIMELINE_STEP;
+    }
+    if keys.just_pressed(KeyCode::Down) {
+        editor.seconds = (editor.seconds - TIMELINE_STEP).max(0.0);
+    }
+
+    if keys.just_pressed(KeyCode::L) {
+        editor.layout.lose_condition = match editor.layout.lose_condition {
+            LoseCondition::AnyDiscovered => LoseCondition::AllDiscovered,
+            LoseCondition::AllDiscovered => LoseCondition::AnyDiscovered,
+        };
+    }
+    if keys.just_pressed(KeyCode::B) {
+        editor.layout.protect_route = !editor.layout.protect_route;
+    }
+
+    if keys.just_pressed(KeyCode::O) {
+        let opened = (editor.opened + 1) % (FLOOR_FILES.len() + 1);
+        editor.open(opened, &asset_server);
+        editor.message = None;
+    }
+
+    if keys.just_pressed(KeyCode::F5) {
+        editor.message = Some(export(&editor.layout, editor.path()));
+    }
+
+    if keys.just_pressed(KeyCode::P) {
+        if editor.layout.towers.is_empty() || editor.layout.entrances.is_empty() {
+            editor.message = Some("editor.incomplete");
+        } else if game_state.set(GameState::InGame).is_ok() {
+            level.level = editor.floor();
+            level.layout = editor.layout.clone();
+            level.file = editor.file.clone().unwrap_or_default();
+            level.test_play = true;
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Escape) {
+        // Fails when a test-play has been started this frame, which then goes first.
+        let _ = game_state.set(GameState::MainMenu);
+    }
+}
+
+fn use_mouse(buttons: Res<Input<MouseButton>>, windows: Res<Windows>, mut editor: ResMut<Editor>) {
+    let position = match cursor_position(windows.primary()) {
+        Some(position) => position,
+        None => return,
+    };
+    if !position.abs().cmplt(PLAY_AREA / 2.0).all() {
+        return;
+    }
+
+    if buttons.just_pressed(MouseButton::Left) {
+        editor.message = None;
+        editor.place(position);
+    } else if buttons.just_pressed(MouseButton::Right) {
+        editor.message = None;
+        editor.remove(position);
+    }
+}
+
+/// Write the layout to the level file, and return the localization key of the outcome.
+#[cfg(not(target_arch = "wasm32"))]
+fn export(layout: &LevelLayout, path: &str) -> &'static str {
+    let contents = match ron::ser::to_string_pretty(layout, ron::ser::PrettyConfig::default()) {
+        Ok(contents) => contents,
+        Err(err) => {
+            warn!("Could not export level: {}", err);
+            return "editor.export_failed";
+        }
+    };
+
+    match std::fs::write(format!("assets/{}", path), contents) {
+        Ok(()) => "editor.exported",
+        Err(err) => {
+            warn!("Could not export level: {}", err);
+            "editor.export_failed"
+        }
+    }
+}
+
+/// The browser can't write to the assets, so log the level file to copy it from the console.
+#[cfg(target_arch = "wasm32")]
+fn export(layout: &LevelLayout, _path: &str) -> &'static str {
+    match ron::ser::to_string_pretty(layout, ron::ser::PrettyConfig::default()) {
+        Ok(contents) => {
+            info!("{}", contents);
+            "editor.logged"
+        }
+        Err(err) => {
+            warn!("Could not export level: {}", err);
+            "editor.export_failed"
+        }
+    }
+}
+
+/// Name of a sprite without its folder and extension.
+fn sprite_name(sprite: &str) -> &str {
+    sprite
+        .trim_start_matches("sprites/")
+        .trim_end_matches(".png")
+}
+
+fn status_text(editor: &Editor, localization: &Localization) -> String {
+    let mut lines = vec![
+        localization.format("editor.file", &[("file", &editor.path())]),
+        localization.format(
+            "editor.tool",
+            &[("tool", &localization.get(editor.tool.name()))],
+        ),
+    ];
+
+    match editor.tool {
+        Tool::Furniture => {
+            let hit_box = match HIT_BOXES[editor.hit_box] {
+                Some(size) => format!("{}x{}", size.x / CELL_SIZE, size.y / CELL_SIZE),
+                None => localization.get("editor.hit_box.cell").to_string(),
+            };
+            lines.push(localization.format(
+                "editor.furniture",
+                &[
+                    ("sprite", &sprite_name(CATALOGUE[editor.item])),
+                    ("scale", &SCALES[editor.scale]),
+                    ("hit_box", &hit_box),
+                ],
+            ));
+            if editor.flipped {
+                lines.push(localization.get("editor.flipped").to_string());
+            }
+        }
+        Tool::Tower => {}
+        Tool::Entrance => {
+            lines.push(
+                localization
+                    .get(EntranceKind::ALL[editor.item].name())
+                    .to_string(),
+            );
+        }
+        Tool::Arrival => {
+            let (sprite, _, _) = COWORKERS[editor.item];
+            lines.push(localization.format(
+                "editor.arrival",
+                &[
+                    ("sprite", &sprite_name(sprite)),
+                    ("seconds", &editor.seconds),
+                ],
+            ));
+            for arrival in &editor.layout.arrivals {
+                let entrance = editor
+                    .layout
+                    .entrances
+                    .get(arrival.entrance)
+                    .map_or("", |entrance| localization.get(entrance.name()));
+                lines.push(localization.format(
+                    "editor.timeline",
+                    &[
+                        ("seconds", &arrival.seconds),
+                        ("sprite", &sprite_name(&arrival.sprite)),
+                        ("entrance", &entrance),
+                    ],
+                ));
+            }
+        }
+    }
+
+    lines.push(
+        localization
+            .get(match editor.layout.lose_condition {
+                LoseCondition::AnyDiscovered => "editor.lose_condition.any",
+                LoseCondition::AllDiscovered => "editor.lose_condition.all",
+            })
+            .to_string(),
+    );
+    lines.push(
+        localization
+            .get(match editor.layout.protect_route {
+                true => "editor.protect_route.on",
+                false => "editor.protect_route.off",
+            })
+            .to_string(),
+    );
+    if let Some(message) = editor.message {
+        lines.push(localization.get(message).to_string());
+    }
+    lines.push(localization.get("editor.help").to_string());
+
+    lines.join("\n")
+}
+
+/// Show the layout and the tool again after it changed.
+fn show_layout(
+    mut commands: Commands,
+    editor: Res<Editor>,
+    asset_server: Res<AssetServer>,
+    localization: Res<Localization>,
+    windows: Res<Windows>,
+    item_query: Query<Entity, With<EditorItem>>,
+    mut text_query: Query<&mut Text, With<EditorText>>,
+) {
+    if !editor.is_changed() {
+        return;
+    }
+
+    for entity in &item_query {
+        commands.entity(entity).despawn();
+    }
+
+    let mut z = -1.0;
+    for wall in &editor.layout.walls {
+        commands.spawn((wall.sprite_bundle(&asset_server, z), EditorItem));
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: HIT_BOX_COLOR,
+                    custom_size: Some(wall.hit_box.unwrap_or(Collider::default().hit_box)),
+                    ..default()
+                },
+                transform: Transform::from_translation(
+                    get_coordinates(wall.row, wall.column).extend(1.0),
+                ),
+                ..default()
+            },
+            EditorItem,
+        ));
+        z += 0.01;
+    }
+
+    for tower in &editor.layout.towers {
+        commands.spawn((
+            SpriteBundle {
+                texture: asset_server.load("sprites/spritesheet_elephant_front_idle.png"),
+                sprite: Sprite {
+                    rect: Some(Rect::new(0.0, 0.0, 32.0, 32.0)),
+                    ..default()
+                },
+                transform: Transform::from_scale(Vec3::splat(4.0))
+                    .with_translation(tower.extend(-1.0)),
+                ..default()
+            },
+            EditorItem,
+        ));
+    }
+
+    // Entrances are just outside the play area, so show their names at its edge.
+    let max = PLAY_AREA / 2.0 - CELL_SIZE / 2.0;
+    for entrance in &editor.layout.entrances {
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    localization.get(entrance.name()),
+                    TextStyle {
+                        font: asset_server.load("fonts/PixeloidSans.ttf"),
+                        font_size: 24.0,
+                        color: Color::rgb(1.0, 0.8, 0.2),
+                    },
+                )
+                .with_alignment(TextAlignment::CENTER),
+                transform: Transform::from_translation(
+                    entrance.position.clamp(-max, max).extend(1.0),
+                ),
+                ..default()
+            },
+            EditorItem,
+        ));
+    }
+
+    let mut cursor = match editor.tool {
+        Tool::Furniture => editor.wall(0, 0).sprite_bundle(&asset_server, 1.0),
+        _ => SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(Vec2::splat(CELL_SIZE)),
+                ..default()
+            },
+            ..default()
+        },
+    };
+    cursor.sprite.color = CURSOR_COLOR;
+    if let Some(position) = cursor_position(windows.primary()) {
+        cursor.transform.translation = snap(position).extend(1.0);
+    }
+    commands.spawn((cursor, EditorCursor, EditorItem));
+
+    for mut text in &mut text_query {
+        text.sections[0].value = status_text(&editor, &localization);
+    }
+}
+
+fn move_cursor(windows: Res<Windows>, mut cursor_query: Query<&mut Transform, With<EditorCursor>>) {
+    if let Some(position) = cursor_position(windows.primary()) {
+        for mut transform in &mut cursor_query {
+            transform.translation = snap(position).extend(transform.translation.z);
+        }
+    }
+}
+
+fn cleanup_editor(
+    mut commands: Commands,
+    entity_query: Query<Entity, Or<(With<EditorItem>, With<Volatile>)>>,
+) {
+    for entity in &entity_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
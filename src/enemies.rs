@@ -1,8 +1,21 @@
 use bevy::prelude::*;
 
 use crate::force::Force;
-use crate::pathfinding::VectorField;
+use crate::pathfinding::{ExitField, VectorField};
 use crate::physics::{Collider, Moving, MovingState};
+use crate::simulation::{
+    simulation_step, SimulationPhase, SimulationRng, SimulationStage, SimulationTime,
+};
+
+/// Distance within which co-workers start to step aside for each other.
+const SEPARATION_RADIUS: f32 = 20.0 * 4.0;
+/// Maximum separation force, applied when two co-workers fully overlap.
+const SEPARATION_NEWTON: f32 = 400.0;
+/// Leaving co-workers are removed once they're this far outside of the screen.
+const OFFSCREEN_MARGIN: f32 = 40.0;
+
+/// Sent when a co-worker has lost interest and walked off the floor.
+pub struct EnemyLeftEvent;
 
 #[derive(Component, Default)]
 pub struct Enemy {
@@ -14,37 +27,83 @@ pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(turn_enemy).add_system(walk_back);
+        app.add_system_set_to_stage(
+            SimulationStage,
+            simulation_step(SimulationPhase::Steer)
+                .with_system(walk_back)
+                .with_system(turn_enemy.after(walk_back)),
+        )
+        .add_system_set_to_stage(
+            SimulationStage,
+            simulation_step(SimulationPhase::React).with_system(leave_floor),
+        )
+        .add_event::<EnemyLeftEvent>();
+    }
+}
+
+/// Boids-style separation: push away from every neighbour within `SEPARATION_RADIUS`, stronger the
+/// closer they are.
+fn get_separation(position: Vec2, neighbours: &[(Entity, Vec2)], entity: Entity) -> Vec2 {
+    let mut separation = Vec2::ZERO;
+
+    for (neighbour, neighbour_position) in neighbours {
+        if *neighbour == entity {
+            continue;
+        }
+
+        let vector = position - *neighbour_position;
+        let distance = vector.length();
+
+        if distance < SEPARATION_RADIUS {
+            // Co-workers on the exact same spot get pushed apart in an arbitrary direction.
+            let direction = if distance > 0.0 {
+                vector / distance
+            } else {
+                Vec2::Y
+            };
+            separation += direction * SEPARATION_NEWTON * (1.0 - distance / SEPARATION_RADIUS);
+        }
     }
+
+    separation
 }
 
 fn turn_enemy(
     mut force_query: Query<(&Force, &Transform)>,
-    mut enemy_query: Query<(&mut Moving, &Transform), With<Enemy>>,
+    mut enemy_query: Query<(Entity, &mut Moving, &Transform), With<Enemy>>,
+    neighbour_query: Query<(Entity, &Transform), (With<Enemy>, With<Collider>)>,
     vector_field: Res<VectorField>,
-    time: Res<Time>,
+    exit_field: Res<ExitField>,
+    simulation_time: Res<SimulationTime>,
+    mut rng: ResMut<SimulationRng>,
 ) {
-    for (mut moving, transform) in &mut enemy_query {
-        // Skip when retracing
-        if moving.state == MovingState::Retrace {
-            continue;
-        }
+    let neighbours: Vec<(Entity, Vec2)> = neighbour_query
+        .iter()
+        .map(|(entity, transform)| (entity, transform.translation.truncate()))
+        .collect();
 
-        // Slowly point enemy towards tower
-        let mut force_sum =
-            vector_field.get_direction(transform.translation.truncate()) * moving.speed.abs();
-
-        // Add external forces
-        for (force, force_transform) in &mut force_query {
-            if let Some(f) = force.get_force(
-                transform.translation.truncate(),
-                force_transform.translation.truncate(),
-            ) {
-                force_sum += f;
+    for (entity, mut moving, transform) in &mut enemy_query {
+        let position = transform.translation.truncate();
+
+        // Slowly point enemy towards tower, or towards the exit when leaving
+        let mut force_sum = match moving.state {
+            MovingState::Normal => vector_field.get_direction(position, &mut **rng),
+            MovingState::Leaving => exit_field.get_direction(position, &mut **rng),
+        } * moving.speed.abs();
+
+        // Add external forces, which leaving enemies no longer care about
+        if moving.state == MovingState::Normal {
+            for (force, force_transform) in &mut force_query {
+                if let Some(f) = force.get_force(position, force_transform.translation.truncate()) {
+                    force_sum += f;
+                }
             }
         }
 
-        let turning_speed = time.delta_seconds() * 5000.0;
+        // Keep some distance from other co-workers
+        force_sum += get_separation(position, &neighbours, entity);
+
+        let turning_speed = simulation_time.delta_seconds() * 5000.0;
         moving.velocity =
             ((turning_speed - 1.0) * moving.velocity + force_sum.extend(0.0)) / turning_speed;
     }
@@ -53,16 +112,36 @@ fn turn_enemy(
 fn walk_back(
     mut commands: Commands,
     mut enemy_query: Query<(Entity, &mut Enemy, &mut Moving)>,
-    time: Res<Time>,
+    simulation_time: Res<SimulationTime>,
 ) {
     for (entity, mut enemy, mut moving) in enemy_query.iter_mut() {
         if moving.state == MovingState::Normal {
-            enemy.timer.tick(time.delta());
+            enemy.timer.tick(simulation_time.delta());
 
             if enemy.timer.finished() {
-                moving.state = MovingState::Retrace;
+                moving.state = MovingState::Leaving;
                 commands.entity(entity).remove::<Collider>();
             }
         }
     }
 }
+
+fn leave_floor(
+    mut commands: Commands,
+    enemy_query: Query<(Entity, &Moving, &Transform), With<Enemy>>,
+    vector_field: Res<VectorField>,
+    exit_field: Res<ExitField>,
+    mut ev_enemy_left: EventWriter<EnemyLeftEvent>,
+) {
+    let bounds = vector_field.size() / 2.0 + OFFSCREEN_MARGIN;
+
+    for (entity, moving, transform) in &enemy_query {
+        let position = transform.translation.truncate();
+        let offscreen = position.x.abs() > bounds.x || position.y.abs() > bounds.y;
+
+        if moving.state == MovingState::Leaving && (offscreen || exit_field.reached(position)) {
+            commands.entity(entity).despawn();
+            ev_enemy_left.send(EnemyLeftEvent);
+        }
+    }
+}
@@ -1,16 +1,21 @@
-use bevy::{prelude::*, sprite::collide_aabb::collide};
-use bevy_kira_audio::prelude::{Audio, *};
+use bevy::{ecs::system::SystemParam, prelude::*, sprite::collide_aabb::collide};
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
-use crate::camera::CameraShake;
+use crate::audio::SoundEffects;
+use crate::camera::{cursor_position, CameraShake, PLAY_AREA};
+use crate::controls::Controls;
 use crate::cost::Points;
 use crate::enemies::Enemy;
 use crate::game::{GameState, Volatile};
-use crate::ghost::Ghost;
+use crate::ghost::{Ghost, PlacementCursor};
 use crate::grid::snap;
+use crate::level::Level;
+use crate::pathfinding::VectorField;
 use crate::physics::{Collider, Solid};
 use crate::sprite::AnimationTimer;
-use crate::ui::UIBar;
+use crate::tuning::Tuning;
+use crate::ui::{UIBar, BAR_WIDTH};
 
 const PASSIVE_COLOR: Color = Color::rgb(0.0, 0.65, 0.0);
 const ATTRACT_COLOR: Color = Color::rgb(0.65, 0.0, 0.0);
@@ -21,7 +26,7 @@ struct ForceSpawnEvent {
     force_type: ForceType,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub enum ForceType {
     Passive,
     Attract,
@@ -39,11 +44,22 @@ impl From<ForceType> for Color {
 }
 
 impl ForceType {
-    pub fn price(&self) -> f32 {
+    /// All items, in the order of the UI bar.
+    pub const ALL: [ForceType; 3] = [ForceType::Passive, ForceType::Attract, ForceType::Repel];
+
+    pub fn price(&self, tuning: &Tuning) -> f32 {
+        match self {
+            ForceType::Passive => tuning.prices.passive,
+            ForceType::Attract => tuning.prices.attract,
+            ForceType::Repel => tuning.prices.repel,
+        }
+    }
+
+    /// Distance within which the item affects co-workers, if it does at all.
+    pub fn influence_radius(&self, tuning: &Tuning) -> Option<f32> {
         match self {
-            ForceType::Passive => 30.0,
-            ForceType::Attract => 50.0,
-            ForceType::Repel => 45.0,
+            ForceType::Passive => None,
+            ForceType::Attract | ForceType::Repel => Some(tuning.influence * 4.0),
         }
     }
 }
@@ -75,101 +91,146 @@ impl Force {
     }
 }
 
+/// Checks whether an item can be placed somewhere.
+#[derive(SystemParam)]
+pub struct Placement<'w, 's> {
+    vector_field: Res<'w, VectorField>,
+    level: Res<'w, Level>,
+    solid_query:
+        Query<'w, 's, (&'static Collider, &'static Transform), (With<Solid>, Without<Enemy>)>,
+    enemy_query:
+        Query<'w, 's, (&'static Collider, &'static Transform), (With<Enemy>, Without<Solid>)>,
+}
+
+impl<'w, 's> Placement<'w, 's> {
+    pub fn is_valid(&self, position: Vec2) -> bool {
+        // Prevent player from placing on a solid object
+        let on_solid = self.solid_query.iter().any(|(collider, transform)| {
+            collide(
+                transform.translation,
+                collider.hit_box,
+                position.extend(0.0),
+                Vec2::new(1.0, 1.0),
+            )
+            .is_some()
+        });
+
+        // Prevent player from placing on an enemy
+        let on_enemy = self.enemy_query.iter().any(|(collider, transform)| {
+            collide(
+                transform.translation,
+                collider.hit_box,
+                position.extend(0.0),
+                Vec2::new(24.0 * 4.0, 24.0 * 4.0),
+            )
+            .is_some()
+        });
+
+        !on_solid && !on_enemy
+    }
+
+    /// The route from each entrance to the tower with an item at the given position, or `None`
+    /// for the entrances it would cut off.
+    ///
+    /// Items placed since the `VectorField` was last updated are taken into account too.
+    pub fn routes(&self, position: Vec2) -> Vec<Option<Vec<Vec2>>> {
+        let solids = self
+            .solid_query
+            .iter()
+            .map(|(collider, transform)| (transform.translation.truncate(), collider.hit_box))
+            .chain([(snap(position), Collider::default().hit_box)]);
+        let field = self.vector_field.with_solids(solids);
+
+        self.level
+            .entrances()
+            .iter()
+            .map(|entrance| field.path(entrance.position))
+            .collect()
+    }
+
+    /// Whether an item at the given position would be rejected for cutting off the tower.
+    pub fn seals_off(&self, routes: &[Option<Vec<Vec2>>]) -> bool {
+        self.level.protects_route() && routes.iter().any(Option::is_none)
+    }
+}
+
 pub struct ForcePlugin;
 
 impl Plugin for ForcePlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<ForceSpawnEvent>()
-            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(mouse_button_input))
-            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(spawn_force));
+            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(place_force))
+            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(spawn_force))
+            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(retune_forces));
     }
 }
 
-fn mouse_button_input(
+/// Place the selected item where the mouse is released, or at the grid cursor on confirm.
+#[allow(clippy::too_many_arguments)]
+fn place_force(
     buttons: Res<Input<MouseButton>>,
+    controls: Controls,
+    cursor: Res<PlacementCursor>,
     mut ev_spawn_force: EventWriter<ForceSpawnEvent>,
     windows: Res<Windows>,
-    mut uibar_query: Query<(&mut UIBar, &Node), With<UIBar>>,
-    solid_query: Query<(&Collider, &Transform), (With<Solid>, Without<Enemy>)>,
-    enemy_query: Query<(&Collider, &Transform), (With<Enemy>, Without<Solid>)>,
+    mut uibar_query: Query<&mut UIBar>,
+    placement: Placement,
     mut camera_query: Query<&mut CameraShake>,
 ) {
     let mut shake = camera_query.single_mut();
-    let (mut uibar, uibar_node) = uibar_query.single_mut();
-    let window = windows.primary();
-    let window_width = window.width();
-    let window_height = window.height();
-
-    if buttons.just_released(MouseButton::Left) {
-        if let Some(force_type) = uibar.selected_force {
-            if let Some(raw_position) = window.cursor_position() {
-                if raw_position.x < uibar_node.size().x {
-                    // Do not summon a force within the UI bar.
-                    return;
-                }
-
-                let position = raw_position - Vec2::new(window_width, window_height) / 2.0;
-
-                // Prevent player from placing on a solid object
-                for (solid_collider, solid_transform) in &solid_query {
-                    if collide(
-                        solid_transform.translation,
-                        solid_collider.hit_box,
-                        position.extend(0.0),
-                        Vec2::new(1.0, 1.0),
-                    )
-                    .is_some()
-                    {
-                        shake.trauma += 0.5;
-                        return;
-                    }
-                }
-
-                // Prevent player from placing on an enemy
-                for (enemy_collider, enemy_transform) in &enemy_query {
-                    if collide(
-                        enemy_transform.translation,
-                        enemy_collider.hit_box,
-                        position.extend(0.0),
-                        Vec2::new(24.0 * 4.0, 24.0 * 4.0),
-                    )
-                    .is_some()
-                    {
-                        shake.trauma += 0.5;
-                        return;
-                    }
-                }
-
-                ev_spawn_force.send(ForceSpawnEvent {
-                    position: snap(position),
-                    force_type,
-                });
+    let mut uibar = uibar_query.single_mut();
 
-                uibar.selected_force = None;
-            }
+    let force_type = match uibar.selected_force {
+        Some(force_type) => force_type,
+        None => return,
+    };
+
+    let position = if buttons.just_released(MouseButton::Left) {
+        match cursor_position(windows.primary()) {
+            // Do not summon a force within the UI bar.
+            Some(position) if position.x < BAR_WIDTH - PLAY_AREA.x / 2.0 => return,
+            Some(position) => position,
+            None => return,
         }
+    } else if controls.confirm() {
+        cursor.position
+    } else {
+        return;
+    };
+
+    if !placement.is_valid(position) || placement.seals_off(&placement.routes(position)) {
+        shake.trauma += 0.5;
+        return;
     }
+
+    ev_spawn_force.send(ForceSpawnEvent {
+        position: snap(position),
+        force_type,
+    });
+
+    uibar.selected_force = None;
 }
 
+#[allow(clippy::too_many_arguments)]
 fn spawn_force(
     mut commands: Commands,
     mut points: ResMut<Points>,
+    tuning: Res<Tuning>,
     mut ev_spawn_force: EventReader<ForceSpawnEvent>,
     asset_server: Res<AssetServer>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
     mut camera_query: Query<&mut CameraShake>,
     ghost_query: Query<Entity, With<Ghost>>,
-    audio: Res<Audio>,
+    sound_effects: SoundEffects,
 ) {
     let mut rng = rand::thread_rng();
-    let influence = 50.0;
 
     for ev in ev_spawn_force.iter() {
         for entity in &ghost_query {
-            commands.entity(entity).despawn();
+            commands.entity(entity).despawn_recursive();
         }
 
-        points.owned -= ev.force_type.price();
+        points.owned -= ev.force_type.price(&tuning);
 
         let mut shake = camera_query.single_mut();
         shake.trauma += 0.3;
@@ -241,7 +302,7 @@ fn spawn_force(
         };
 
         if let Some(s) = sound {
-            audio.play(s);
+            sound_effects.play_at(s, ev.position.extend(0.0));
         }
 
         let texture_atlas_handle = texture_atlases.add(texture_atlas);
@@ -258,8 +319,8 @@ fn spawn_force(
             },
             AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)),
             Force {
-                newton: 500.0,
-                influence,
+                newton: tuning.newton,
+                influence: tuning.influence,
                 force_type: ev.force_type,
             },
             Collider::default(),
@@ -268,3 +329,15 @@ fn spawn_force(
         ));
     }
 }
+
+/// Give placed items the strength and reach of changed tuning values.
+fn retune_forces(tuning: Res<Tuning>, mut force_query: Query<&mut Force, Without<Enemy>>) {
+    if !tuning.is_changed() {
+        return;
+    }
+
+    for mut force in &mut force_query {
+        force.newton = tuning.newton;
+        force.influence = tuning.influence;
+    }
+}
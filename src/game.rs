@@ -1,7 +1,10 @@
 use bevy::prelude::*;
 use std::time::Duration;
 
+use crate::camera::PLAY_AREA;
+use crate::dialogue::{DialogueRunner, StartDialogueEvent};
 use crate::level::Level;
+use crate::localization::Localization;
 use crate::sprite::AnimationTimer;
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
@@ -13,6 +16,8 @@ pub enum GameState {
     Won,
     GameOver,
     End,
+    /// Level editor, see `editor`.
+    Editor,
 }
 
 #[derive(Component)]
@@ -52,6 +57,7 @@ impl Plugin for GamePlugin {
             .add_system_set(
                 SystemSet::on_enter(GameState::Start)
                     .with_system(setup_start_text)
+                    .with_system(start_cutscene)
                     .with_system(cleanup_volatile),
             )
             .add_system_set(SystemSet::on_update(GameState::Start).with_system(update_timer_text))
@@ -76,7 +82,7 @@ impl Plugin for GamePlugin {
 
 fn cleanup_volatile(mut commands: Commands, volatile_query: Query<Entity, With<Volatile>>) {
     for entity in &volatile_query {
-        commands.entity(entity).despawn();
+        commands.entity(entity).despawn_recursive();
     }
 }
 
@@ -94,6 +100,7 @@ fn set_win_timer(mut commands: Commands) {
 
 fn reset_level(mut level: ResMut<Level>) {
     level.level = 1;
+    level.test_play = false;
 }
 
 fn next_level(mut level: ResMut<Level>) {
@@ -105,18 +112,29 @@ fn tick_state_timer(
     mut game_state: ResMut<State<GameState>>,
     mut q: Query<(Entity, &mut StateTimer)>,
     time: Res<Time>,
+    level: Res<Level>,
 ) {
     for (entity, mut timer) in q.iter_mut() {
         timer.timer.tick(time.delta());
 
         if timer.timer.finished() {
             commands.entity(entity).despawn();
-            game_state.set(GameState::Start).unwrap();
+            // Test-plays of the editor return to it.
+            let next = match level.test_play {
+                true => GameState::Editor,
+                false => GameState::Start,
+            };
+            game_state.set(next).unwrap();
         }
     }
 }
 
-fn setup_start_text(mut commands: Commands, level: Res<Level>, asset_server: Res<AssetServer>) {
+fn setup_start_text(
+    mut commands: Commands,
+    level: Res<Level>,
+    localization: Res<Localization>,
+    asset_server: Res<AssetServer>,
+) {
     commands
         .spawn((
             NodeBundle {
@@ -132,7 +150,7 @@ fn setup_start_text(mut commands: Commands, level: Res<Level>, asset_server: Res
         .with_children(|parent| {
             parent.spawn(
                 TextBundle::from_section(
-                    level.title(),
+                    localization.get(level.title()),
                     TextStyle {
                         font: asset_server.load("fonts/PixeloidSans.ttf"),
                         font_size: 40.0,
@@ -148,7 +166,7 @@ fn setup_start_text(mut commands: Commands, level: Res<Level>, asset_server: Res
 
             parent.spawn((
                 TextBundle::from_section(
-                    "08:59",
+                    localization.get("start.clock.before"),
                     TextStyle {
                         font: asset_server.load("fonts/PixeloidSans.ttf"),
                         font_size: 40.0,
@@ -170,7 +188,7 @@ fn setup_start_text(mut commands: Commands, level: Res<Level>, asset_server: Res
 
             if let Some(help_text) = level.help_text() {
                 parent.spawn((TextBundle::from_section(
-                    format!("Hint: {}", help_text),
+                    localization.format("start.hint", &[("hint", &localization.get(help_text))]),
                     TextStyle {
                         font: asset_server.load("fonts/PixeloidSans.ttf"),
                         font_size: 40.0,
@@ -189,21 +207,43 @@ fn setup_start_text(mut commands: Commands, level: Res<Level>, asset_server: Res
         });
 }
 
+fn start_cutscene(
+    level: Res<Level>,
+    dialogue_runner: Res<DialogueRunner>,
+    mut ev_start_dialogue: EventWriter<StartDialogueEvent>,
+) {
+    if let Some(path) = level.cutscene() {
+        if !dialogue_runner.has_seen(path) {
+            ev_start_dialogue.send(StartDialogueEvent {
+                path: path.to_string(),
+                then: None,
+            });
+        }
+    }
+}
+
 fn update_timer_text(
     mut text_query: Query<(&mut Text, &mut TimeText)>,
     time: Res<Time>,
     mut game_state: ResMut<State<GameState>>,
+    dialogue_runner: Res<DialogueRunner>,
+    localization: Res<Localization>,
 ) {
     let (mut text, mut time_text) = text_query.single_mut();
 
+    // The clock only starts ticking after the cutscene.
+    if dialogue_runner.is_active() {
+        return;
+    }
+
     time_text.timer.tick(time.delta());
 
     if time_text.timer.finished() {
         game_state.set(GameState::InGame).unwrap();
     } else if time_text.timer.elapsed_secs() < 3.5 {
-        text.sections[0].value = "08:59 AM".to_string();
+        text.sections[0].value = localization.get("start.clock.before").to_string();
     } else {
-        text.sections[0].value = "09:00 AM".to_string();
+        text.sections[0].value = localization.get("start.clock.after").to_string();
     }
 }
 
@@ -239,6 +279,7 @@ fn show_win_animation(
 fn show_end_screen(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    localization: Res<Localization>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
 ) {
     let texture_handle = asset_server.load("sprites/Elephant_end.png");
@@ -261,7 +302,7 @@ fn show_end_screen(
 
     commands.spawn((
         TextBundle::from_section(
-            "And nobody was able to address the elephant in the room! Thanks for playing <3 Art by Guus, Audio by Tim, Code by Joris",
+            localization.get("credits"),
             TextStyle {
                 font: asset_server.load("fonts/PixeloidSans.ttf"),
                 font_size: 70.0,
@@ -273,14 +314,14 @@ fn show_end_screen(
             position_type: PositionType::Absolute,
             position: UiRect {
                 top: Val::Px(20.0),
-                left: Val::Px(1280.0),
+                left: Val::Px(PLAY_AREA.x),
                 ..default()
             },
             ..default()
         }),
         Volatile,
         ScrollText {
-            left: 1280.0,
+            left: PLAY_AREA.x,
             speed: 250.0,
         },
     ));
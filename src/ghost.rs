@@ -1,37 +1,173 @@
-use bevy::prelude::*;
+//! Preview of the selected item at the cursor.
+//!
+//! The ghost is tinted green where the item can be placed and red where it cannot, or when the
+//! player cannot afford it. Coffee and stacks of work show the area they influence.
+//!
 
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+
+use crate::camera::{cursor_position, PLAY_AREA};
+use crate::controls::Controls;
+use crate::cost::Points;
+use crate::force::{ForceType, Placement};
 use crate::game::GameState;
-use crate::grid::snap;
+use crate::grid::{snap, CELL_SIZE};
+use crate::localization::Localization;
+use crate::route::{preview_route, PredictedRoute};
+use crate::tuning::Tuning;
+use crate::ui::BAR_WIDTH;
+
+const VALID_COLOR: Color = Color::rgba(0.4, 1.0, 0.4, 0.5);
+const INVALID_COLOR: Color = Color::rgba(1.0, 0.3, 0.3, 0.5);
+const PRICE_COLOR: Color = Color::rgba(0.9, 0.9, 0.9, 0.9);
+const UNAFFORDABLE_COLOR: Color = Color::rgba(1.0, 0.3, 0.3, 0.9);
+
+/// Alpha of the area an item influences.
+const RANGE_ALPHA: f32 = 0.15;
+
+#[derive(Component)]
+pub struct Ghost {
+    pub force_type: ForceType,
+}
 
 #[derive(Component)]
-pub struct Ghost;
+struct GhostPrice;
+
+/// Where the selected item will be placed. Follows the mouse, or moves a cell at a time with the
+/// keyboard or a gamepad.
+#[derive(Default, Resource)]
+pub struct PlacementCursor {
+    pub position: Vec2,
+}
 
 pub struct GhostPlugin;
 
 impl Plugin for GhostPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_set(SystemSet::on_update(GameState::InGame).with_system(move_ghost))
+        app.init_resource::<PlacementCursor>()
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(move_cursor)
+                    .with_system(move_ghost.after(move_cursor))
+                    .with_system(decorate_ghost)
+                    // Uses the route predicted for the cursor in the same frame.
+                    .with_system(preview_placement.after(move_cursor).after(preview_route)),
+            )
             .add_system_set(SystemSet::on_enter(GameState::InGame).with_system(cleanup_ghosts))
             .add_system_set(SystemSet::on_exit(GameState::InGame).with_system(cleanup_ghosts));
     }
 }
 
-fn move_ghost(mut ghost_query: Query<&mut Transform, With<Ghost>>, windows: Res<Windows>) {
+fn move_cursor(
+    mut cursor: ResMut<PlacementCursor>,
+    mut controls: Controls,
+    mut cursor_moved: EventReader<CursorMoved>,
+    windows: Res<Windows>,
+) {
+    if cursor_moved.iter().count() > 0 {
+        if let Some(position) = cursor_position(windows.primary()) {
+            cursor.position = position;
+        }
+    }
+
+    let movement = controls.movement();
+    if movement != IVec2::ZERO {
+        // Keep the cursor within the play area, right of the UI bar.
+        let max = PLAY_AREA / 2.0 - CELL_SIZE / 2.0;
+        let min = -max + Vec2::new(BAR_WIDTH, 0.0);
+        let position = snap(cursor.position) + movement.as_vec2() * CELL_SIZE;
+        cursor.position = position.clamp(min, max);
+    }
+}
+
+fn move_ghost(mut ghost_query: Query<&mut Transform, With<Ghost>>, cursor: Res<PlacementCursor>) {
     for mut transform in &mut ghost_query {
-        let window = windows.primary();
-        let window_width = window.width();
-        let window_height = window.height();
+        transform.translation = snap(cursor.position).extend(0.0);
+    }
+}
+
+/// Show the price and influence of a newly selected item around its ghost.
+fn decorate_ghost(
+    mut commands: Commands,
+    ghost_query: Query<(Entity, &Ghost, &Transform), Added<Ghost>>,
+    asset_server: Res<AssetServer>,
+    localization: Res<Localization>,
+    tuning: Res<Tuning>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for (entity, ghost, transform) in &ghost_query {
+        // Children are scaled along with the sprite of the ghost, so undo that.
+        let unscaled = Transform::from_scale(transform.scale.recip());
+
+        commands.entity(entity).with_children(|parent| {
+            if let Some(radius) = ghost.force_type.influence_radius(&tuning) {
+                let mut color = Color::from(ghost.force_type);
+                color.set_a(RANGE_ALPHA);
+
+                parent.spawn(MaterialMesh2dBundle {
+                    mesh: meshes.add(shape::Circle::new(radius).into()).into(),
+                    material: materials.add(ColorMaterial::from(color)),
+                    transform: unscaled.with_translation(Vec3::new(0.0, 0.0, -0.1)),
+                    ..default()
+                });
+            }
+
+            let above = (CELL_SIZE / 2.0 + 12.0) / transform.scale.y;
+            parent.spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        localization
+                            .format("price", &[("price", &ghost.force_type.price(&tuning))]),
+                        TextStyle {
+                            font: asset_server.load("fonts/PixeloidSans.ttf"),
+                            font_size: 20.0,
+                            color: PRICE_COLOR,
+                        },
+                    )
+                    .with_alignment(TextAlignment::CENTER),
+                    transform: unscaled.with_translation(Vec3::new(0.0, above, 0.1)),
+                    ..default()
+                },
+                GhostPrice,
+            ));
+        });
+    }
+}
+
+/// Tint the ghost by whether it can be placed at the cursor, with the same checks as placing it.
+fn preview_placement(
+    cursor: Res<PlacementCursor>,
+    points: Res<Points>,
+    tuning: Res<Tuning>,
+    placement: Placement,
+    predicted_route: Res<PredictedRoute>,
+    mut ghost_query: Query<(&Ghost, &mut TextureAtlasSprite, Option<&Children>)>,
+    mut price_query: Query<&mut Text, With<GhostPrice>>,
+) {
+    for (ghost, mut sprite, children) in &mut ghost_query {
+        let affordable = ghost.force_type.price(&tuning) <= points.owned;
+
+        let valid = placement.is_valid(cursor.position) && !predicted_route.sealed_off;
 
-        if let Some(raw_position) = window.cursor_position() {
-            let position = raw_position - Vec2::new(window_width, window_height) / 2.0;
+        sprite.color = match affordable && valid {
+            true => VALID_COLOR,
+            false => INVALID_COLOR,
+        };
 
-            transform.translation = snap(position).extend(0.0);
+        for child in children.into_iter().flatten() {
+            if let Ok(mut text) = price_query.get_mut(*child) {
+                text.sections[0].style.color = match affordable {
+                    true => PRICE_COLOR,
+                    false => UNAFFORDABLE_COLOR,
+                };
+            }
         }
     }
 }
 
 fn cleanup_ghosts(mut commands: Commands, ghost_query: Query<Entity, With<Ghost>>) {
     for entity in &ghost_query {
-        commands.entity(entity).despawn();
+        commands.entity(entity).despawn_recursive();
     }
 }
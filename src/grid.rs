@@ -5,6 +5,9 @@ const CELL_HEIGHT: f32 = 24.0;
 const GRID_ROWS: usize = 24;
 const GRID_COLUMNS: usize = 24;
 
+/// Distance between two positions items can be placed at.
+pub const CELL_SIZE: f32 = CELL_WIDTH * 4.0;
+
 pub fn get_coordinates(row: usize, column: usize) -> Vec2 {
     Vec2::new(
         (row as f32 - GRID_ROWS as f32 / 2.0) * CELL_HEIGHT * 4.0,
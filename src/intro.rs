@@ -1,35 +1,19 @@
 use bevy::prelude::*;
-use bevy_kira_audio::prelude::{Audio, *};
 
+use crate::dialogue::StartDialogueEvent;
 use crate::game::{GameState, Volatile};
 use crate::grid::get_coordinates;
 use crate::level::setup_floor;
 use crate::sprite::AnimationTimer;
 
-#[derive(Component)]
-struct Conversation;
-
-#[derive(Component)]
-struct TextView;
-
-#[derive(Debug, Default, Resource)]
-pub struct ConversationLine {
-    pub number: u8,
-    played_sound: bool,
-}
-
 pub struct IntroPlugin;
 
 impl Plugin for IntroPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(ConversationLine::default())
-            .add_system_set(SystemSet::on_enter(GameState::Intro).with_system(setup_floor))
+        app.add_system_set(SystemSet::on_enter(GameState::Intro).with_system(setup_floor))
             .add_system_set(SystemSet::on_enter(GameState::Intro).with_system(setup_scene))
             .add_system_set(SystemSet::on_enter(GameState::Intro).with_system(setup_animations))
-            .add_system_set(SystemSet::on_enter(GameState::Intro).with_system(setup_textview))
-            .add_system_set(SystemSet::on_update(GameState::Intro).with_system(update_conversation))
-            .add_system_set(SystemSet::on_update(GameState::Intro).with_system(next_line))
-            .add_system_set(SystemSet::on_exit(GameState::Intro).with_system(cleanup_textview));
+            .add_system_set(SystemSet::on_enter(GameState::Intro).with_system(start_conversation));
     }
 }
 
@@ -144,94 +128,9 @@ fn setup_animations(
     ));
 }
 
-fn setup_textview(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands
-        .spawn((
-            NodeBundle {
-                style: Style {
-                    size: Size::new(Val::Percent(100.0), Val::Px(200.0)),
-                    border: UiRect::all(Val::Px(2.0)),
-                    position: UiRect {
-                        bottom: Val::Px(0.0),
-                        ..default()
-                    },
-                    position_type: PositionType::Absolute,
-                    justify_content: JustifyContent::Center,
-                    ..default()
-                },
-                background_color: Color::rgb(0.1, 0.1, 0.1).into(),
-                ..default()
-            },
-            TextView,
-        ))
-        .with_children(|parent| {
-            parent.spawn((
-                TextBundle::from_section(
-                    "",
-                    TextStyle {
-                        font: asset_server.load("fonts/PixeloidSans.ttf"),
-                        font_size: 50.0,
-                        color: Color::WHITE,
-                    },
-                )
-                .with_style(Style {
-                    align_self: AlignSelf::Center,
-                    ..default()
-                }),
-                Conversation,
-            ));
-        });
-}
-
-fn update_conversation(
-    mut conversation_line: ResMut<ConversationLine>,
-    mut text_query: Query<&mut Text, With<Conversation>>,
-    mut game_state: ResMut<State<GameState>>,
-    asset_server: Res<AssetServer>,
-    audio: Res<Audio>,
-) {
-    let mut text = text_query.single_mut();
-    let color_bob = Color::FUCHSIA;
-    let color_manager = Color::rgb(0.542, 0.674, 1.0);
-
-    let (sound_file, color, line) = match conversation_line.number {
-        0 => (Some("sounds/Manager_Intro1.mp3"), color_manager, "Look, Dumbo.."),
-        1 => (Some("sounds/Bob_Intro1.mp3"), color_bob, "...it's Bob..."),
-        2 => (Some("sounds/Manager_Intro2.mp3"), color_manager, "I'm only gonna tell you once."),
-        3 => (Some("sounds/Manager_Intro3.mp3"), color_manager, "It wasn't my plan to hire an elephant to do\noptimisations around the department, but\nhere we are."),
-        4 => (Some("sounds/Manager_Intro4.mp3"), color_manager, "As supply manager it's your job\nto optimize the department.."),
-        5 => (Some("sounds/Manager_Intro2.mp3"), color_manager, "..but DON'T cause any distractions!"),
-        6 => (Some("sounds/Manager_Intro5.mp3"), color_manager, "If I see a co-worker hanging around\nat your station you will be fired."),
-        7 => (Some("sounds/Manager_Intro6.mp3"), color_manager, "I don't care how you do it but make\nsure to hide that face of yours."),
-        8 => (Some("sounds/Manager_Intro7.mp3"), color_manager, "Employees here aren't accustomed\nseeing elephants everyday.."),
-        9 => (Some("sounds/Manager_Intro8.mp3"), color_manager, "..and it would make my day a lot\nbetter if I don't see that dopey\nface of yours the entire day."),
-        10 => (Some("sounds/Manager_Intro1.mp3"), color_manager, "Enjoy your workday, Dumbo!"),
-        11 => (None, color_bob, "..."),
-        _ => {
-            game_state.set(GameState::Start).unwrap();
-            (None, Color::RED, "???")
-        }
-    };
-
-    text.sections[0].value = line.to_string();
-    text.sections[0].style.color = color;
-
-    if let Some(file) = sound_file {
-        if !conversation_line.played_sound {
-            audio.play(asset_server.load(file));
-            conversation_line.played_sound = true;
-        }
-    }
-}
-
-fn next_line(buttons: Res<Input<MouseButton>>, mut conversation_line: ResMut<ConversationLine>) {
-    if buttons.just_pressed(MouseButton::Left) {
-        conversation_line.number += 1;
-        conversation_line.played_sound = false;
-    }
-}
-
-fn cleanup_textview(mut commands: Commands, textview_query: Query<Entity, With<TextView>>) {
-    let textview = textview_query.single();
-    commands.entity(textview).despawn_recursive();
+fn start_conversation(mut ev_start_dialogue: EventWriter<StartDialogueEvent>) {
+    ev_start_dialogue.send(StartDialogueEvent {
+        path: "dialogues/intro.dialogue.ron".to_string(),
+        then: Some(GameState::Start),
+    });
 }
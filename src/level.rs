@@ -1,44 +1,428 @@
-use bevy::prelude::*;
+//! Floors of the office, which are read from level files.
+//!
+//! A level file is a RON file in `assets/levels` with the `.level.ron` extension. It defines the
+//! furniture on the grid, the towers, the entrances and the co-workers who arrive through them,
+//! for example:
+//!
+//! ```ron
+//! (
+//!     walls: [
+//!         (sprite: "sprites/desk_B.png", row: 7, column: 13, scale: 1.5),
+//!         (
+//!             sprite: "sprites/conference_desk.png",
+//!             row: 13,
+//!             column: 11,
+//!             scale: 2.0,
+//!             flipped: true,
+//!             hit_box: Some((480.0, 72.0)),
+//!         ),
+//!     ],
+//!     towers: [(0.0, 0.0)],
+//!     entrances: [(kind: Elevator, position: (-688.0, 0.0))],
+//!     arrivals: [
+//!         (
+//!             seconds: 3.0,
+//!             sprite: "sprites/spritesheet_NPC01_M_walk.png",
+//!             sprite_size: (16.0, 24.0),
+//!         ),
+//!         (
+//!             seconds: 10.0,
+//!             sprite: "sprites/spritesheet_NPC04_M_walk.png",
+//!             sprite_size: (16.0, 32.0),
+//!             force_type: Repel,
+//!         ),
+//!     ],
+//!     lose_condition: AnyDiscovered,
+//!     protect_route: true,
+//! )
+//! ```
+//!
+//! Hit boxes, scales and flips of furniture are optional, as is the entrance of a co-worker. A
+//! level file can also have an `economy`, like the ones of the tuning file (see `tuning`).
+//!
+//! The floors of the game are the files in `FLOOR_FILES`, which are loaded at startup. They can be
+//! opened in the editor like any other level file.
+//!
+//! When the level file of the floor which is played changes, its furniture is replaced and the
+//! co-workers who haven't arrived yet are taken from the file again (see `LevelReloadedEvent`).
+//! This also works while test-playing from the editor.
+//!
+
+use bevy::{
+    asset::{AssetLoader, BoxedFuture, Error, LoadContext, LoadState, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+};
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
+use crate::camera::PLAY_AREA;
+use crate::force::ForceType;
 use crate::game::{GameState, Volatile};
 use crate::grid::get_coordinates;
 use crate::physics::{Collider, Solid};
+use crate::tuning::Economy;
+
+/// Level files of the floors of the game, in order.
+pub const FLOOR_FILES: [&str; 4] = [
+    "levels/floor_1.level.ron",
+    "levels/floor_2.level.ron",
+    "levels/floor_3.level.ron",
+    "levels/floor_4.level.ron",
+];
+
+/// Distance of entrances from the edge of the play area.
+const ENTRANCE_MARGIN: f32 = 4.0 * 12.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum EntranceKind {
+    Elevator,
+    Stairwell,
+    SideDoor,
+}
+
+impl EntranceKind {
+    pub const ALL: [EntranceKind; 3] = [
+        EntranceKind::Elevator,
+        EntranceKind::Stairwell,
+        EntranceKind::SideDoor,
+    ];
+
+    /// Localization key of the name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            EntranceKind::Elevator => "entrance.elevator",
+            EntranceKind::Stairwell => "entrance.stairwell",
+            EntranceKind::SideDoor => "entrance.side_door",
+        }
+    }
+}
+
+/// A way onto the floor, just outside the play area. Co-workers enter through it, and leave
+/// through the nearest one when they lose interest.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct Entrance {
+    pub kind: EntranceKind,
+    pub position: Vec2,
+}
+
+impl Entrance {
+    /// An entrance just outside the edge of the play area nearest to a position.
+    pub fn at_edge(kind: EntranceKind, position: Vec2) -> Self {
+        let half = PLAY_AREA / 2.0;
+        let distance = half - position.abs();
+
+        let position = match distance.x < distance.y {
+            true => Vec2::new((half.x + ENTRANCE_MARGIN) * position.x.signum(), position.y),
+            false => Vec2::new(position.x, (half.y + ENTRANCE_MARGIN) * position.y.signum()),
+        };
+
+        Entrance { kind, position }
+    }
+
+    /// Localization key of the name.
+    pub fn name(&self) -> &'static str {
+        self.kind.name()
+    }
+
+    /// Direction from the entrance onto the floor.
+    pub fn inward(&self) -> Vec2 {
+        match self.position.x.abs() > PLAY_AREA.x / 2.0 {
+            true => Vec2::new(-self.position.x.signum(), 0.0),
+            false => Vec2::new(0.0, -self.position.y.signum()),
+        }
+    }
+}
+
+/// When a floor with several towers is lost.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum LoseCondition {
+    /// As soon as the elephant is discovered at any of the towers.
+    #[default]
+    AnyDiscovered,
+    /// Once the elephant is discovered at every tower; co-workers head for the remaining ones in
+    /// the meantime.
+    AllDiscovered,
+}
+
+/// A piece of furniture on the grid.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Wall {
+    pub sprite: String,
+    pub row: usize,
+    pub column: usize,
+    /// Size relative to the other furniture.
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    #[serde(default)]
+    pub flipped: bool,
+    /// Size of the area co-workers can't walk through, or the size of a cell if not set.
+    #[serde(default)]
+    pub hit_box: Option<Vec2>,
+}
+
+impl Wall {
+    pub fn sprite_bundle(&self, asset_server: &AssetServer, z: f32) -> SpriteBundle {
+        SpriteBundle {
+            texture: asset_server.load(&self.sprite),
+            transform: Transform {
+                translation: get_coordinates(self.row, self.column).extend(z),
+                rotation: match self.flipped {
+                    true => Quat::from_rotation_y(std::f32::consts::PI),
+                    false => Quat::default(),
+                },
+                scale: Vec3::splat(4.0 * self.scale),
+            },
+            ..default()
+        }
+    }
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+/// A co-worker who arrives on the floor.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Arrival {
+    /// Time after the start of the floor, in seconds.
+    pub seconds: f32,
+    pub sprite: String,
+    /// Size of a frame of the spritesheet.
+    pub sprite_size: Vec2,
+    #[serde(default = "default_force_type")]
+    pub force_type: ForceType,
+    /// Index of the entrance the co-worker comes through.
+    #[serde(default)]
+    pub entrance: usize,
+}
+
+fn default_force_type() -> ForceType {
+    ForceType::Passive
+}
+
+/// Contents of a level file.
+#[derive(Clone, Debug, Deserialize, Serialize, TypeUuid)]
+#[uuid = "8c4a1f0e-2b7d-4d53-9a86-5e0f3b1c7d24"]
+#[serde(default)]
+pub struct LevelLayout {
+    pub walls: Vec<Wall>,
+    /// Positions of the towers.
+    pub towers: Vec<Vec2>,
+    pub entrances: Vec<Entrance>,
+    pub arrivals: Vec<Arrival>,
+    pub lose_condition: LoseCondition,
+    /// Whether placements which cut off every route to a tower are rejected. Without it, the
+    /// floor is a sandbox in which players can box in the elephant.
+    pub protect_route: bool,
+    /// Economy of the floor, instead of the one of the tuning file.
+    pub economy: Option<Economy>,
+}
+
+impl Default for LevelLayout {
+    fn default() -> Self {
+        LevelLayout {
+            walls: Vec::new(),
+            towers: Vec::new(),
+            entrances: Vec::new(),
+            arrivals: Vec::new(),
+            lose_condition: LoseCondition::default(),
+            protect_route: true,
+            economy: None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let layout: LevelLayout = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(layout));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+/// Keeps the level files of the floors loaded, so they are ready when a floor starts.
+#[derive(Default, Resource)]
+pub struct FloorFiles(Vec<Handle<LevelLayout>>);
+
+impl FloorFiles {
+    /// Whether the level files of all floors have been read, so the game can be started.
+    pub fn loaded(&self, asset_server: &AssetServer) -> bool {
+        asset_server.get_group_load_state(self.0.iter().map(|handle| handle.id()))
+            == LoadState::Loaded
+    }
+}
+
+/// Sent when the level file of the floor which is played has changed, once `Level::layout` has
+/// been replaced.
+pub struct LevelReloadedEvent;
+
+/// Furniture of a floor, which is replaced when its level file changes.
+#[derive(Component)]
+struct Furniture;
 
 #[derive(Debug, Default, Resource)]
 pub struct Level {
     pub level: u8,
+    /// Layout of the floor which is played.
+    pub layout: LevelLayout,
+    /// Level file the layout was read from.
+    pub file: Handle<LevelLayout>,
+    /// Whether the layout is test-played from the editor, instead of the floor of `level`.
+    pub test_play: bool,
 }
 
 impl Level {
-    pub fn title(&self) -> String {
+    /// Localization key of the name of the floor.
+    pub fn title(&self) -> &'static str {
+        if self.test_play {
+            return "level.custom.title";
+        }
+
+        match self.level {
+            1 => "level.1.title",
+            2 => "level.2.title",
+            3 => "level.3.title",
+            4 => "level.4.title",
+            _ => "level.unknown.title",
+        }
+    }
+
+    /// Dialogue to play before the floor starts.
+    pub fn cutscene(&self) -> Option<&'static str> {
         match self.level {
-            1 => "1st floor: IT department".to_string(),
-            2 => "2nd floor: Sales department".to_string(),
-            3 => "3rd floor: Conference room".to_string(),
-            4 => "4th floor: Executive room".to_string(),
-            _ => "???".to_string(),
+            4 => Some("dialogues/floor_4.dialogue.ron"),
+            _ => None,
         }
     }
 
-    pub fn help_text(&self) -> Option<String> {
+    /// Localization key of the hint shown before the floor starts.
+    pub fn help_text(&self) -> Option<&'static str> {
         match self.level {
-            1 => Some("Place boxes to block co-workers".to_string()),
-            2 => Some("Coffee attracts co-workers".to_string()),
-            3 => Some("People try to avoid the manager".to_string()),
+            1 => Some("level.1.hint"),
+            2 => Some("level.2.hint"),
+            3 => Some("level.3.hint"),
             _ => None,
         }
     }
+
+    /// Entrances of the floor. Waves refer to them by their index.
+    pub fn entrances(&self) -> &[Entrance] {
+        &self.layout.entrances
+    }
+
+    /// Entrance of the given index, or the first one if there is no such entrance. `None` when
+    /// the floor has no entrances at all.
+    pub fn entrance(&self, index: usize) -> Option<&Entrance> {
+        self.entrances()
+            .get(index)
+            .or_else(|| self.entrances().first())
+    }
+
+    /// Positions of the towers co-workers are after.
+    pub fn towers(&self) -> &[Vec2] {
+        &self.layout.towers
+    }
+
+    pub fn lose_condition(&self) -> LoseCondition {
+        self.layout.lose_condition
+    }
+
+    /// Whether placements which cut off every route to the tower are rejected.
+    pub fn protects_route(&self) -> bool {
+        self.layout.protect_route
+    }
 }
 
 pub struct LevelPlugin;
 
 impl Plugin for LevelPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(Level { level: 1 })
-            .add_system_set(SystemSet::on_enter(GameState::InGame).with_system(setup_walls))
-            .add_system_set(SystemSet::on_enter(GameState::InGame).with_system(setup_floor));
+        app.insert_resource(Level {
+            level: 1,
+            ..default()
+        })
+        .add_asset::<LevelLayout>()
+        .init_asset_loader::<LevelLoader>()
+        .add_event::<LevelReloadedEvent>()
+        .add_startup_system(load_floor_files)
+        .add_system_set(SystemSet::on_exit(GameState::Start).with_system(load_floor))
+        .add_system_set(SystemSet::on_enter(GameState::InGame).with_system(setup_walls))
+        .add_system_set(SystemSet::on_update(GameState::InGame).with_system(reload_level))
+        .add_system_set(SystemSet::on_enter(GameState::InGame).with_system(setup_floor));
+    }
+}
+
+fn load_floor_files(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let handles = FLOOR_FILES
+        .iter()
+        .map(|file| asset_server.load(*file))
+        .collect();
+    commands.insert_resource(FloorFiles(handles));
+}
+
+/// Take the layout of the floor which is about to be played from its level file.
+fn load_floor(
+    mut level: ResMut<Level>,
+    floor_files: Res<FloorFiles>,
+    layouts: Res<Assets<LevelLayout>>,
+) {
+    let file = (level.level as usize)
+        .checked_sub(1)
+        .and_then(|index| floor_files.0.get(index))
+        .cloned()
+        .unwrap_or_default();
+
+    level.layout = match layouts.get(&file) {
+        Some(layout) => layout.clone(),
+        None => {
+            error!("No level file for floor {}", level.level);
+            LevelLayout::default()
+        }
+    };
+    level.file = file;
+    level.test_play = false;
+}
+
+/// Take the layout from the level file again when it changes, and replace the furniture.
+fn reload_level(
+    mut commands: Commands,
+    mut level: ResMut<Level>,
+    mut ev_asset: EventReader<AssetEvent<LevelLayout>>,
+    mut ev_reloaded: EventWriter<LevelReloadedEvent>,
+    layouts: Res<Assets<LevelLayout>>,
+    furniture_query: Query<Entity, With<Furniture>>,
+    asset_server: Res<AssetServer>,
+) {
+    let modified = ev_asset
+        .iter()
+        .any(|ev| matches!(ev, AssetEvent::Modified { handle } if *handle == level.file));
+    let layout = match layouts.get(&level.file) {
+        Some(layout) if modified => layout.clone(),
+        _ => return,
+    };
+
+    for entity in &furniture_query {
+        commands.entity(entity).despawn_recursive();
     }
+    spawn_walls(&mut commands, &layout.walls, &asset_server);
+
+    info!("Reloaded the level file of floor {}", level.level);
+    level.layout = layout;
+    ev_reloaded.send(LevelReloadedEvent);
 }
 
 pub fn setup_floor(mut commands: Commands, asset_server: Res<AssetServer>) {
@@ -70,351 +454,19 @@ pub fn setup_floor(mut commands: Commands, asset_server: Res<AssetServer>) {
 }
 
 fn setup_walls(mut commands: Commands, level: Res<Level>, asset_server: Res<AssetServer>) {
-    let walls = match level.level {
-        1 => vec![
-            // Upper wall
-            (None, 12, 16, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 13, 16, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 14, 16, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 15, 16, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 16, 16, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 17, 16, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 18, 16, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 19, 16, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 12, 15, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 13, 15, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 14, 15, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 15, 15, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 16, 15, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 17, 15, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 18, 15, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 19, 15, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 11, 15, "sprites/plant_A.png", 1.0, false),
-            // Desk
-            (
-                None,
-                6,
-                14,
-                "sprites/Cubicle_screen_corner_A.png",
-                1.0,
-                true,
-            ),
-            (
-                None,
-                7,
-                14,
-                "sprites/Cubicle_screen_square_B.png",
-                1.0,
-                false,
-            ),
-            (
-                None,
-                8,
-                14,
-                "sprites/Cubicle_screen_square_A.png",
-                1.0,
-                false,
-            ),
-            (None, 6, 13, "sprites/Cubicle_screen_side.png", 1.0, false),
-            (None, 6, 14, "sprites/Cubicle_screen_side.png", 1.0, false),
-            (None, 7, 13, "sprites/desk_B.png", 1.5, false),
-            (None, 7, 12, "sprites/office_chair_back.png", 2.0, false),
-            // Desk
-            (
-                None,
-                10,
-                14,
-                "sprites/Cubicle_screen_corner_A.png",
-                1.0,
-                true,
-            ),
-            (
-                None,
-                11,
-                14,
-                "sprites/Cubicle_screen_square_B.png",
-                1.0,
-                false,
-            ),
-            (
-                None,
-                12,
-                14,
-                "sprites/Cubicle_screen_square_A.png",
-                1.0,
-                false,
-            ),
-            (None, 10, 13, "sprites/Cubicle_screen_side.png", 1.0, false),
-            (None, 10, 14, "sprites/Cubicle_screen_side.png", 1.0, false),
-            (None, 11, 13, "sprites/desk_D.png", 1.5, false),
-            // Desk
-            (
-                None,
-                10,
-                11,
-                "sprites/Cubicle_screen_corner_B.png",
-                1.0,
-                true,
-            ),
-            (
-                None,
-                11,
-                11,
-                "sprites/Cubicle_screen_square_A.png",
-                1.0,
-                false,
-            ),
-            (
-                None,
-                12,
-                11,
-                "sprites/Cubicle_screen_square_A.png",
-                1.0,
-                false,
-            ),
-            (None, 10, 10, "sprites/Cubicle_screen_side.png", 1.0, false),
-            (None, 10, 11, "sprites/Cubicle_screen_side.png", 1.0, false),
-            (None, 11, 10, "sprites/desk_C.png", 1.5, false),
-            // Desk
-            (
-                None,
-                14,
-                14,
-                "sprites/Cubicle_screen_corner_A.png",
-                1.0,
-                true,
-            ),
-            (
-                None,
-                15,
-                14,
-                "sprites/Cubicle_screen_square_B.png",
-                1.0,
-                false,
-            ),
-            (
-                None,
-                16,
-                14,
-                "sprites/Cubicle_screen_square_A.png",
-                1.0,
-                false,
-            ),
-            (None, 14, 13, "sprites/Cubicle_screen_side.png", 1.0, false),
-            (None, 14, 14, "sprites/Cubicle_screen_side.png", 1.0, false),
-            (None, 15, 13, "sprites/desk_D.png", 1.5, false),
-            // Desk
-            (
-                None,
-                14,
-                11,
-                "sprites/Cubicle_screen_corner_A.png",
-                1.0,
-                true,
-            ),
-            (
-                None,
-                15,
-                11,
-                "sprites/Cubicle_screen_square_B.png",
-                1.0,
-                true,
-            ),
-            (
-                None,
-                16,
-                11,
-                "sprites/Cubicle_screen_square_B.png",
-                1.0,
-                false,
-            ),
-            (None, 14, 10, "sprites/Cubicle_screen_side.png", 1.0, false),
-            (None, 14, 11, "sprites/Cubicle_screen_side.png", 1.0, false),
-            (None, 15, 10, "sprites/desk_B.png", 1.5, false),
-            // Objects
-            (None, 16, 12, "sprites/plant_B.png", 1.0, false),
-            (None, 11, 12, "sprites/BoxB.png", 1.5, false),
-        ],
-        2 => vec![
-            // Upper wall
-            (None, 4, 16, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 5, 16, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 6, 16, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 7, 16, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 8, 16, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 9, 16, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 10, 16, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 11, 16, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 4, 15, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 5, 15, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 6, 15, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 7, 15, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 8, 15, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 9, 15, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 10, 15, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 11, 15, "sprites/archive_cabinet.png", 1.1, false),
-            (None, 12, 15, "sprites/plant_A.png", 1.0, false),
-            // Desk
-            (
-                None,
-                6,
-                14,
-                "sprites/Cubicle_screen_corner_A.png",
-                1.0,
-                true,
-            ),
-            (
-                None,
-                7,
-                14,
-                "sprites/Cubicle_screen_square_B.png",
-                1.0,
-                false,
-            ),
-            (
-                None,
-                8,
-                14,
-                "sprites/Cubicle_screen_square_A.png",
-                1.0,
-                false,
-            ),
-            (None, 6, 13, "sprites/Cubicle_screen_side.png", 1.0, false),
-            (None, 6, 14, "sprites/Cubicle_screen_side.png", 1.0, false),
-            (None, 7, 13, "sprites/desk_B.png", 1.5, false),
-            (None, 7, 12, "sprites/office_chair_back.png", 2.0, false),
-            // Desk
-            (
-                None,
-                10,
-                14,
-                "sprites/Cubicle_screen_corner_A.png",
-                1.0,
-                true,
-            ),
-            (
-                None,
-                11,
-                14,
-                "sprites/Cubicle_screen_square_B.png",
-                1.0,
-                false,
-            ),
-            (
-                None,
-                12,
-                14,
-                "sprites/Cubicle_screen_square_A.png",
-                1.0,
-                false,
-            ),
-            (None, 10, 13, "sprites/Cubicle_screen_side.png", 1.0, false),
-            (None, 10, 14, "sprites/Cubicle_screen_side.png", 1.0, false),
-            (None, 11, 13, "sprites/desk_D.png", 1.5, false),
-            // Desk
-            (
-                None,
-                14,
-                11,
-                "sprites/Cubicle_screen_corner_A.png",
-                1.0,
-                true,
-            ),
-            (
-                None,
-                15,
-                11,
-                "sprites/Cubicle_screen_square_B.png",
-                1.0,
-                true,
-            ),
-            (
-                None,
-                16,
-                11,
-                "sprites/Cubicle_screen_square_B.png",
-                1.0,
-                false,
-            ),
-            (None, 14, 10, "sprites/Cubicle_screen_side.png", 1.0, false),
-            (None, 14, 11, "sprites/Cubicle_screen_side.png", 1.0, false),
-            (None, 15, 10, "sprites/desk_B.png", 1.5, false),
-            // Objects
-            (None, 17, 15, "sprites/plant_B.png", 1.0, false),
-            (None, 18, 14, "sprites/plant_A.png", 1.0, true),
-        ],
-        3 => vec![
-            (None, 11, 15, "sprites/office_chair.png", 2.0, false),
-            (None, 12, 15, "sprites/office_chair.png", 2.0, false),
-            (None, 13, 15, "sprites/office_chair.png", 2.0, false),
-            (None, 14, 15, "sprites/office_chair.png", 2.0, false),
-            (None, 15, 15, "sprites/office_chair.png", 2.0, false),
-            (
-                Some(Vec2::new(5.0 * 4.0 * 24.0, 4.0 * 18.0)),
-                13,
-                14,
-                "sprites/conference_desk.png",
-                2.0,
-                true,
-            ),
-            (
-                Some(Vec2::new(5.0 * 4.0 * 24.0, 4.0 * 18.0)),
-                13,
-                11,
-                "sprites/conference_desk.png",
-                2.0,
-                true,
-            ),
-            (None, 10, 11, "sprites/plant_B.png", 1.0, false),
-            (None, 10, 14, "sprites/plant_A.png", 1.0, true),
-            (None, 11, 10, "sprites/office_chair_back.png", 2.0, false),
-            (None, 12, 10, "sprites/office_chair_back.png", 2.0, false),
-            (None, 13, 10, "sprites/office_chair_back.png", 2.0, false),
-            (None, 14, 10, "sprites/office_chair_back.png", 2.0, false),
-            (None, 15, 10, "sprites/office_chair_back.png", 2.0, false),
-        ],
-        4 => vec![
-            (None, 13, 15, "sprites/office_chair.png", 2.0, false),
-            (
-                Some(Vec2::new(5.0 * 4.0 * 24.0, 4.0 * 18.0)),
-                13,
-                14,
-                "sprites/conference_desk.png",
-                2.0,
-                true,
-            ),
-            (None, 10, 14, "sprites/plant_A.png", 1.0, false),
-            (None, 16, 14, "sprites/plant_B.png", 1.0, true),
-        ],
-        _ => Vec::new(),
-    };
+    spawn_walls(&mut commands, &level.layout.walls, &asset_server);
+}
 
+fn spawn_walls(commands: &mut Commands, walls: &[Wall], asset_server: &AssetServer) {
     let mut z = -1.0;
-    for (some_hit_box, row, column, sprite, extra_scale, flipped) in walls {
-        let collider = match some_hit_box {
-            Some(hit_box) => Collider {
-                hit_box,
-                ..default()
-            },
-            _ => Collider::default(),
-        };
-
-        let coordinates = get_coordinates(row, column);
-
+    for wall in walls {
         commands.spawn((
-            SpriteBundle {
-                texture: asset_server.load(sprite),
-                transform: Transform {
-                    translation: coordinates.extend(z),
-                    rotation: match flipped {
-                        true => Quat::from_rotation_y(std::f32::consts::PI),
-                        false => Quat::default(),
-                    },
-                    scale: Vec3::splat(4.0 * extra_scale),
-                },
+            wall.sprite_bundle(asset_server, z),
+            Collider {
+                hit_box: wall.hit_box.unwrap_or(Collider::default().hit_box),
                 ..default()
             },
-            collider,
+            Furniture,
             Volatile,
             Solid,
         ));
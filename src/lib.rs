@@ -3,7 +3,10 @@ use bevy::{app::PluginGroupBuilder, prelude::*};
 
 pub mod audio;
 pub mod camera;
+pub mod controls;
 pub mod cost;
+pub mod dialogue;
+pub mod editor;
 pub mod enemies;
 pub mod force;
 pub mod game;
@@ -11,28 +14,42 @@ pub mod ghost;
 pub mod grid;
 pub mod intro;
 pub mod level;
+pub mod localization;
 pub mod menu;
 pub mod pathfinding;
 pub mod physics;
+pub mod route;
+pub mod settings;
+pub mod simulation;
 pub mod sprite;
 pub mod tower;
+pub mod tuning;
 pub mod ui;
+pub mod vision;
 pub mod wave;
 use audio::AudioPlugin;
 use camera::CameraPlugin;
 use cost::CostPlugin;
+use dialogue::DialoguePlugin;
+use editor::EditorPlugin;
 use enemies::EnemyPlugin;
 use force::ForcePlugin;
 use game::GamePlugin;
 use ghost::GhostPlugin;
 use intro::IntroPlugin;
 use level::LevelPlugin;
+use localization::LocalizationPlugin;
 use menu::MenuPlugin;
 use pathfinding::VectorFieldPlugin;
 use physics::PhysicsPlugin;
+use route::RoutePlugin;
+use settings::SettingsPlugin;
+use simulation::SimulationPlugin;
 use sprite::SpritePlugin;
 use tower::TowerPlugin;
+use tuning::TuningPlugin;
 use ui::UIPlugin;
+use vision::VisionPlugin;
 use wave::WavePlugin;
 
 pub struct GamePlugins;
@@ -40,20 +57,28 @@ pub struct GamePlugins;
 impl PluginGroup for GamePlugins {
     fn build(self) -> PluginGroupBuilder {
         PluginGroupBuilder::start::<Self>()
+            .add(SimulationPlugin)
+            .add(LocalizationPlugin)
+            .add(SettingsPlugin)
+            .add(TuningPlugin)
             .add(CameraPlugin)
             .add(AudioPlugin)
             .add(UIPlugin)
             .add(MenuPlugin)
+            .add(DialoguePlugin)
             .add(IntroPlugin)
             .add(GhostPlugin)
+            .add(RoutePlugin)
             .add(CostPlugin)
             .add(LevelPlugin)
             .add(VectorFieldPlugin)
             .add(SpritePlugin)
             .add(TowerPlugin)
+            .add(VisionPlugin)
             .add(EnemyPlugin)
             .add(WavePlugin)
             .add(GamePlugin)
+            .add(EditorPlugin)
             .add(PhysicsPlugin)
             .add(ForcePlugin)
     }
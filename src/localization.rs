@@ -0,0 +1,169 @@
+//! Translations of all text shown in the game.
+//!
+//! Every language has a string table in `assets/locales`, a RON map from keys to text. The tables
+//! are embedded in the binary, so text is available from the first frame. Text can contain
+//! `{name}` placeholders, which are filled in by `Localization::format`.
+//!
+
+use std::fmt::Display;
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    Dutch,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Dutch];
+
+    fn table(&self) -> &'static str {
+        match self {
+            Language::English => include_str!("../assets/locales/en.ron"),
+            Language::Dutch => include_str!("../assets/locales/nl.ron"),
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Language::English => Language::Dutch,
+            Language::Dutch => Language::English,
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct Localization {
+    language: Language,
+    tables: HashMap<Language, HashMap<String, String>>,
+}
+
+impl Default for Localization {
+    fn default() -> Self {
+        let tables = Language::ALL
+            .iter()
+            .map(|language| {
+                let table = ron::de::from_str(language.table()).unwrap_or_else(|err| {
+                    panic!("Invalid string table for {:?}: {}", language, err)
+                });
+                (*language, table)
+            })
+            .collect();
+
+        Self {
+            language: Language::default(),
+            tables,
+        }
+    }
+}
+
+impl Localization {
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    pub fn set_language(&mut self, language: Language) {
+        self.language = language;
+    }
+
+    /// Text of a key in the current language, falling back to English and then the key itself.
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        [self.language, Language::English]
+            .iter()
+            .find_map(|language| self.tables[language].get(key))
+            .map_or_else(
+                || {
+                    warn!("Missing localization key: {}", key);
+                    key
+                },
+                |text| text.as_str(),
+            )
+    }
+
+    /// Text of a key with its `{name}` placeholders replaced by the given values.
+    pub fn format(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        args.iter()
+            .fold(self.get(key).to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{}}}", name), &value.to_string())
+            })
+    }
+}
+
+/// Text which is translated again when the language changes.
+#[derive(Component)]
+pub struct LocalizedText(pub &'static str);
+
+pub struct LocalizationPlugin;
+
+impl Plugin for LocalizationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Localization>()
+            .add_system(update_localized_text);
+    }
+}
+
+fn update_localized_text(
+    localization: Res<Localization>,
+    mut text_query: Query<(&mut Text, &LocalizedText)>,
+) {
+    if !localization.is_changed() {
+        return;
+    }
+
+    for (mut text, localized_text) in &mut text_query {
+        text.sections[0].value = localization.get(localized_text.0).to_string();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::dialogue::DialogueScript;
+
+    #[test]
+    fn every_key_exists_in_every_table() {
+        let localization = Localization::default();
+        let english = &localization.tables[&Language::English];
+
+        for language in Language::ALL {
+            let table = &localization.tables[&language];
+
+            for key in english.keys() {
+                assert!(table.contains_key(key), "{:?} misses {}", language, key);
+            }
+            for key in table.keys() {
+                assert!(
+                    english.contains_key(key),
+                    "{:?} has unknown {}",
+                    language,
+                    key
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn every_dialogue_line_has_a_key() {
+        let localization = Localization::default();
+        let english = &localization.tables[&Language::English];
+        let directory = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/dialogues");
+
+        for entry in fs::read_dir(directory).unwrap() {
+            let path = entry.unwrap().path();
+            let script: DialogueScript = ron::de::from_bytes(&fs::read(&path).unwrap()).unwrap();
+
+            let keys = script.lines.iter().flat_map(|line| {
+                let choices = line.choices.iter().map(|choice| &choice.text);
+                std::iter::once(&line.text).chain(choices)
+            });
+            for key in keys {
+                assert!(english.contains_key(key), "{:?} misses {}", path, key);
+            }
+        }
+    }
+}
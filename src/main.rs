@@ -10,12 +10,16 @@ fn main() {
                 .set(WindowPlugin {
                     window: WindowDescriptor {
                         title: "Trumpet Trainee".to_string(),
-                        resizable: false,
                         ..default()
                     },
                     ..default()
                 })
-                .set(ImagePlugin::default_nearest()),
+                .set(ImagePlugin::default_nearest())
+                // Reload changed assets, like the tuning and level files. The browser can't.
+                .set(AssetPlugin {
+                    watch_for_changes: cfg!(not(target_arch = "wasm32")),
+                    ..default()
+                }),
         )
         .add_plugin(AudioPlugin)
         .add_plugins(GamePlugins)
@@ -1,6 +1,10 @@
 use bevy::prelude::*;
 
+use crate::controls::Controls;
 use crate::game::GameState;
+use crate::level::FloorFiles;
+use crate::localization::{Localization, LocalizedText};
+use crate::settings::{SettingsButton, SettingsMenu};
 
 #[derive(Component)]
 pub struct MainMenuRoot;
@@ -17,7 +21,11 @@ impl Plugin for MenuPlugin {
     }
 }
 
-fn show_main_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn show_main_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    localization: Res<Localization>,
+) {
     commands
         .spawn((
             NodeBundle {
@@ -31,9 +39,9 @@ fn show_main_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
             MainMenuRoot,
         ))
         .with_children(|parent| {
-            parent.spawn(
+            parent.spawn((
                 TextBundle::from_section(
-                    "Trumpet Trainee",
+                    localization.get("menu.title"),
                     TextStyle {
                         font: asset_server.load("fonts/Franchise.ttf"),
                         font_size: 150.0,
@@ -45,11 +53,12 @@ fn show_main_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
                     margin: UiRect::all(Val::Auto),
                     ..default()
                 }),
-            );
+                LocalizedText("menu.title"),
+            ));
 
-            parent.spawn(
+            parent.spawn((
                 TextBundle::from_section(
-                    "Click to start",
+                    localization.get("menu.start"),
                     TextStyle {
                         font: asset_server.load("fonts/PixeloidSans.ttf"),
                         font_size: 50.0,
@@ -64,13 +73,72 @@ fn show_main_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
                     },
                     ..default()
                 }),
-            );
+                LocalizedText("menu.start"),
+            ));
+
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            align_self: AlignSelf::Center,
+                            padding: UiRect::all(Val::Px(10.0)),
+                            margin: UiRect {
+                                top: Val::Px(50.0),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        background_color: Color::rgb(0.2, 0.2, 0.2).into(),
+                        ..default()
+                    },
+                    SettingsButton,
+                ))
+                .with_children(|button| {
+                    button.spawn((
+                        TextBundle::from_section(
+                            localization.get("menu.settings"),
+                            TextStyle {
+                                font: asset_server.load("fonts/PixeloidSans.ttf"),
+                                font_size: 30.0,
+                                color: Color::WHITE,
+                            },
+                        ),
+                        LocalizedText("menu.settings"),
+                    ));
+                });
         });
 }
 
-fn mouse_button_input(mut game_state: ResMut<State<GameState>>, buttons: Res<Input<MouseButton>>) {
-    if buttons.just_released(MouseButton::Left) {
-        game_state.set(GameState::Intro).unwrap();
+/// Start the game on a click or confirm, unless it was on one of the buttons or the settings
+/// screen. Waits for the level files of the floors, which would be played empty otherwise.
+#[allow(clippy::too_many_arguments)]
+fn mouse_button_input(
+    mut game_state: ResMut<State<GameState>>,
+    asset_server: Res<AssetServer>,
+    floor_files: Res<FloorFiles>,
+    buttons: Res<Input<MouseButton>>,
+    controls: Controls,
+    button_query: Query<&Interaction, With<Button>>,
+    settings_query: Query<(), With<SettingsMenu>>,
+    mut pressed: Local<bool>,
+) {
+    if buttons.just_pressed(MouseButton::Left) {
+        *pressed = settings_query.is_empty()
+            && button_query
+                .iter()
+                .all(|interaction| *interaction == Interaction::None);
+    }
+
+    let confirmed = controls.confirm() && settings_query.is_empty();
+
+    if !floor_files.loaded(&asset_server) {
+        return;
+    }
+
+    if (buttons.just_released(MouseButton::Left) && *pressed) || confirmed {
+        *pressed = false;
+        // Fails when the editor has been opened this frame already.
+        let _ = game_state.set(GameState::Intro);
     }
 }
 
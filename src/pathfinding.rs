@@ -8,22 +8,33 @@
 //!
 //! 1. An N by M grid of Cell objects is initialized.
 //! 2. For each solid and static object, a `solid` flag is set for each corresponding Cell.
-//! 3. Starting with the Cell corresponding with the Tower, the movement property of each
+//! 3. Starting with the Cells corresponding with the Towers, the movement property of each
 //!    non-solid neighbouring Cell is set towards the root cell.
 //! 4. Step 3 is repeated for each non-solid neighbouring Cell (first N/E/S/W, then diagonally).
 //!
+//! The same algorithm is used for a second vector map, the `ExitField`, which starts at the
+//! entrances of the floor instead of at the tower. Co-workers who lose interest use it to find
+//! their way out.
+//!
 
-use bevy::{ecs::query::QuerySingleError, prelude::*, time::FixedTimestep};
+use bevy::{prelude::*, time::FixedTimestep};
 use log::debug;
 use rand::Rng;
-use std::{cmp, fmt};
+use std::{cmp, collections::VecDeque, fmt};
 
+use crate::camera::PLAY_AREA;
+use crate::level::Level;
 use crate::physics::{Collider, Solid};
 use crate::tower::Tower;
 
 const GRID_ROWS: usize = 40;
 const GRID_COLUMNS: usize = 40;
 
+/// Region around an exit which counts as having reached it.
+const EXIT_SIZE: Vec2 = Vec2::new(4.0 * 24.0, 4.0 * 24.0);
+/// Distance to an exit at which a co-worker has walked through it.
+const EXIT_RADIUS: f32 = 12.0;
+
 #[derive(Debug, Copy, Clone)]
 enum Movement {
     Up,
@@ -39,16 +50,12 @@ enum Movement {
 #[derive(Debug, Resource)]
 pub struct VectorField {
     cells: [[Cell; GRID_COLUMNS]; GRID_ROWS],
-    width: f32,
-    height: f32,
 }
 
 impl Default for VectorField {
     fn default() -> Self {
         VectorField {
             cells: [[Cell::default(); GRID_COLUMNS]; GRID_ROWS],
-            width: 1280.0,
-            height: 720.0,
         }
     }
 }
@@ -79,9 +86,7 @@ impl fmt::Display for VectorField {
 }
 
 impl VectorField {
-    pub fn get_direction(&self, from: Vec2) -> Vec2 {
-        let mut rng = rand::thread_rng();
-
+    pub fn get_direction(&self, from: Vec2, rng: &mut impl Rng) -> Vec2 {
         let (row, column) = self.position_to_index(from);
         let cell: &Cell = &self.cells[row][column];
 
@@ -98,40 +103,84 @@ impl VectorField {
         }
     }
 
+    /// Size of the area covered by the field.
+    pub fn size(&self) -> Vec2 {
+        PLAY_AREA
+    }
+
+    pub fn is_goal(&self, position: Vec2) -> bool {
+        let (row, column) = self.position_to_index(position);
+        self.cells[row][column].goal
+    }
+
+    /// Copy of the field as it would be with the given solid objects, e.g. an item about to be
+    /// placed, as positions and hit boxes.
+    pub fn with_solids(&self, solids: impl IntoIterator<Item = (Vec2, Vec2)>) -> VectorField {
+        let mut field = VectorField { cells: self.cells };
+        for cell in field.cells.iter_mut().flatten() {
+            cell.movement = None;
+        }
+
+        for (position, hit_box) in solids {
+            field.mark_region(position, hit_box, false);
+        }
+        field.generate();
+        field
+    }
+
+    /// Centers of the cells along the route from a position to the goal, or `None` if the goal
+    /// cannot be reached from there.
+    pub fn path(&self, from: Vec2) -> Option<Vec<Vec2>> {
+        let (mut row, mut column) = self.position_to_index(from);
+        let mut path = vec![self.index_to_position(row, column)];
+
+        while !self.cells[row][column].goal {
+            (row, column) = match self.cells[row][column].movement? {
+                Movement::Up => (row - 1, column),
+                Movement::Down => (row + 1, column),
+                Movement::Left => (row, column - 1),
+                Movement::Right => (row, column + 1),
+                Movement::UpRight => (row - 1, column + 1),
+                Movement::UpLeft => (row - 1, column - 1),
+                Movement::DownRight => (row + 1, column + 1),
+                Movement::DownLeft => (row + 1, column - 1),
+            };
+            path.push(self.index_to_position(row, column));
+        }
+
+        Some(path)
+    }
+
     /// Generate the VectorField.
     ///
-    /// Should only be executed _after_ the solid/tower cells have been set.
+    /// Should only be executed _after_ the solid/goal cells have been set.
     fn generate(&mut self) {
-        let mut queue = Vec::new();
+        let mut queue = VecDeque::new();
 
-        // Add the tower cells to the queue.
+        // Add the goal cells to the queue.
         for row in 0..GRID_ROWS {
             for column in 0..GRID_COLUMNS {
                 let cell: &Cell = &self.cells[row][column];
-                if cell.tower {
-                    queue.push((row, column));
+                if cell.goal {
+                    queue.push_back((row, column));
                 }
             }
         }
 
         // For each cell on the queue, set the direction of each neighbour towards it.
-        while !queue.is_empty() {
-            let (row, column) = queue
-                .pop()
-                .expect("queue should not be empty at this point");
-
+        while let Some((row, column)) = queue.pop_front() {
             if row > 0 {
                 let neighbour_n: &mut Cell = &mut self.cells[row - 1][column];
                 if neighbour_n.movement.is_none() && !neighbour_n.solid {
                     neighbour_n.movement = Some(Movement::Down);
-                    queue.insert(0, (row - 1, column));
+                    queue.push_back((row - 1, column));
                 }
 
                 if column > 0 {
                     let neighbour_nw: &mut Cell = &mut self.cells[row - 1][column - 1];
                     if neighbour_nw.movement.is_none() && !neighbour_nw.solid {
                         neighbour_nw.movement = Some(Movement::DownRight);
-                        queue.insert(0, (row - 1, column - 1));
+                        queue.push_back((row - 1, column - 1));
                     }
                 }
 
@@ -139,7 +188,7 @@ impl VectorField {
                     let neighbour_ne: &mut Cell = &mut self.cells[row - 1][column + 1];
                     if neighbour_ne.movement.is_none() && !neighbour_ne.solid {
                         neighbour_ne.movement = Some(Movement::DownLeft);
-                        queue.insert(0, (row - 1, column + 1));
+                        queue.push_back((row - 1, column + 1));
                     }
                 }
             }
@@ -148,14 +197,14 @@ impl VectorField {
                 let neighbour_s: &mut Cell = &mut self.cells[row + 1][column];
                 if neighbour_s.movement.is_none() && !neighbour_s.solid {
                     neighbour_s.movement = Some(Movement::Up);
-                    queue.insert(0, (row + 1, column));
+                    queue.push_back((row + 1, column));
                 }
 
                 if column > 0 {
                     let neighbour_sw: &mut Cell = &mut self.cells[row + 1][column - 1];
                     if neighbour_sw.movement.is_none() && !neighbour_sw.solid {
                         neighbour_sw.movement = Some(Movement::UpRight);
-                        queue.insert(0, (row + 1, column - 1));
+                        queue.push_back((row + 1, column - 1));
                     }
                 }
 
@@ -163,7 +212,7 @@ impl VectorField {
                     let neighbour_se: &mut Cell = &mut self.cells[row + 1][column + 1];
                     if neighbour_se.movement.is_none() && !neighbour_se.solid {
                         neighbour_se.movement = Some(Movement::UpLeft);
-                        queue.insert(0, (row + 1, column + 1));
+                        queue.push_back((row + 1, column + 1));
                     }
                 }
             }
@@ -172,7 +221,7 @@ impl VectorField {
                 let neighbour_w: &mut Cell = &mut self.cells[row][column - 1];
                 if neighbour_w.movement.is_none() && !neighbour_w.solid {
                     neighbour_w.movement = Some(Movement::Right);
-                    queue.insert(0, (row, column - 1));
+                    queue.push_back((row, column - 1));
                 }
             }
 
@@ -180,7 +229,7 @@ impl VectorField {
                 let neighbour_e: &mut Cell = &mut self.cells[row][column + 1];
                 if neighbour_e.movement.is_none() && !neighbour_e.solid {
                     neighbour_e.movement = Some(Movement::Left);
-                    queue.insert(0, (row, column + 1));
+                    queue.push_back((row, column + 1));
                 }
             }
         }
@@ -191,15 +240,38 @@ impl VectorField {
         self.cells = field.cells;
     }
 
+    /// Set the solid flag for each cell covered by the given hit box, and the goal flag if needed.
+    fn mark_region(&mut self, position: Vec2, hit_box: Vec2, goal: bool) {
+        let upperleft = position - (hit_box / 1.8);
+        let lowerright = position + (hit_box / 1.8);
+
+        let (rows, columns) = self.get_region_indices(upperleft, lowerright);
+        for row in &rows {
+            for column in &columns {
+                let cell: &mut Cell = &mut self.cells[*row][*column];
+                cell.solid = true;
+                cell.goal |= goal;
+            }
+        }
+    }
+
+    #[allow(clippy::manual_clamp)]
     fn position_to_index(&self, position: Vec2) -> (usize, usize) {
-        let row = GRID_ROWS as f32 * ((position.y / self.height) + 0.5);
-        let column = GRID_COLUMNS as f32 * ((position.x / self.width) + 0.5);
+        let row = GRID_ROWS as f32 * ((position.y / PLAY_AREA.y) + 0.5);
+        let column = GRID_COLUMNS as f32 * ((position.x / PLAY_AREA.x) + 0.5);
         (
             cmp::min(cmp::max(row as usize, 0), GRID_ROWS - 1),
             cmp::min(cmp::max(column as usize, 0), GRID_COLUMNS - 1),
         )
     }
 
+    fn index_to_position(&self, row: usize, column: usize) -> Vec2 {
+        Vec2::new(
+            ((column as f32 + 0.5) / GRID_COLUMNS as f32 - 0.5) * PLAY_AREA.x,
+            ((row as f32 + 0.5) / GRID_ROWS as f32 - 0.5) * PLAY_AREA.y,
+        )
+    }
+
     fn get_region_indices(
         &mut self,
         upperleft: Vec2,
@@ -218,10 +290,41 @@ impl VectorField {
 #[derive(Debug, Default, Copy, Clone)]
 struct Cell {
     solid: bool,
-    tower: bool,
+    goal: bool,
     movement: Option<Movement>,
 }
 
+/// A VectorField which leads to the nearest exit of the floor.
+#[derive(Debug, Default, Resource)]
+pub struct ExitField {
+    field: VectorField,
+    exits: Vec<Vec2>,
+}
+
+impl ExitField {
+    pub fn get_direction(&self, from: Vec2, rng: &mut impl Rng) -> Vec2 {
+        if !self.field.is_goal(from) {
+            return self.field.get_direction(from, rng);
+        }
+
+        // Close to an exit, so head straight for it.
+        self.exits
+            .iter()
+            .map(|exit| *exit - from)
+            .min_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
+            .unwrap_or_default()
+            .normalize_or_zero()
+    }
+
+    /// Whether a position is at one of the exits. Exits inside of the floor are left this way,
+    /// instead of off the screen.
+    pub fn reached(&self, position: Vec2) -> bool {
+        self.exits
+            .iter()
+            .any(|exit| exit.distance(position) < EXIT_RADIUS)
+    }
+}
+
 pub struct VectorFieldPlugin;
 
 impl Plugin for VectorFieldPlugin {
@@ -232,7 +335,7 @@ impl Plugin for VectorFieldPlugin {
                 .with_system(update_vector_field),
         )
         .add_startup_system(create_vector_field)
-        .add_system(update_screen_dimensions);
+        .add_startup_system(create_exit_field);
     }
 }
 
@@ -240,55 +343,50 @@ fn create_vector_field(mut commands: Commands) {
     commands.insert_resource(VectorField::default());
 }
 
-fn update_screen_dimensions(mut vector_field: ResMut<VectorField>, windows: Res<Windows>) {
-    let window = windows.primary();
-    vector_field.width = window.width() as f32;
-    vector_field.height = window.height() as f32;
+fn create_exit_field(mut commands: Commands) {
+    commands.insert_resource(ExitField::default());
 }
 
 fn update_vector_field(
     mut vector_field: ResMut<VectorField>,
-    tower_query: Query<(&Collider, &Transform), With<Tower>>,
+    mut exit_field: ResMut<ExitField>,
+    level: Res<Level>,
+    tower_query: Query<(&Tower, &Collider, &Transform)>,
     solid_query: Query<(&Collider, &Transform), (With<Solid>, Without<Tower>)>,
 ) {
     vector_field.reset();
+    exit_field.field.reset();
+
+    // Set tower flags; co-workers head for the nearest tower which has not been discovered yet.
+    for (tower, tower_collider, tower_transform) in &tower_query {
+        let position = tower_transform.translation.truncate();
+        vector_field.mark_region(position, tower_collider.hit_box, !tower.discovered);
+        exit_field
+            .field
+            .mark_region(position, tower_collider.hit_box, false);
+    }
 
-    // Set tower flags
-    match tower_query.get_single() {
-        Ok((tower_collider, tower_transform)) => {
-            let tower_upperleft =
-                tower_transform.translation.truncate() - (tower_collider.hit_box / 1.8);
-            let tower_lowerright =
-                tower_transform.translation.truncate() + (tower_collider.hit_box / 1.8);
-
-            let (rows, columns) =
-                vector_field.get_region_indices(tower_upperleft, tower_lowerright);
-            for row in &rows {
-                for column in &columns {
-                    let cell: &mut Cell = &mut vector_field.cells[*row][*column];
-                    cell.solid = true;
-                    cell.tower = true;
-                }
-            }
-        }
-        Err(QuerySingleError::NoEntities(_)) => {}
-        Err(QuerySingleError::MultipleEntities(_)) => error!("Multiple towers!"),
+    // Set exit flags
+    exit_field.exits = level
+        .entrances()
+        .iter()
+        .map(|entrance| entrance.position)
+        .collect();
+    let exits = exit_field.exits.clone();
+    for exit in exits {
+        exit_field.field.mark_region(exit, EXIT_SIZE, true);
     }
 
     // Set solid flags
     for (solid_collider, solid_transform) in &solid_query {
-        let upperleft = solid_transform.translation.truncate() - (solid_collider.hit_box / 1.8);
-        let lowerright = solid_transform.translation.truncate() + (solid_collider.hit_box / 1.8);
-
-        let (rows, columns) = vector_field.get_region_indices(upperleft, lowerright);
-        for row in &rows {
-            for column in &columns {
-                let cell: &mut Cell = &mut vector_field.cells[*row][*column];
-                cell.solid = true;
-            }
-        }
+        let position = solid_transform.translation.truncate();
+        vector_field.mark_region(position, solid_collider.hit_box, false);
+        exit_field
+            .field
+            .mark_region(position, solid_collider.hit_box, false);
     }
 
     vector_field.generate();
+    exit_field.field.generate();
     debug!("{}", *vector_field);
 }
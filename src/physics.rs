@@ -5,7 +5,7 @@ use bevy::{
 use log::warn;
 
 use crate::game::GameState;
-use crate::grid::{get_coordinates, get_indeces};
+use crate::simulation::{simulation_step, SimulationPhase, SimulationStage, SimulationTime};
 
 #[derive(Component)]
 pub struct Solid;
@@ -28,7 +28,8 @@ impl Default for Collider {
 #[derive(PartialEq, Eq)]
 pub enum MovingState {
     Normal,
-    Retrace,
+    /// On the way to the nearest exit.
+    Leaving,
 }
 
 #[derive(Component)]
@@ -36,7 +37,6 @@ pub struct Moving {
     pub state: MovingState,
     pub velocity: Vec3,
     pub speed: f32,
-    pub route_history: Vec<(usize, usize)>,
     last_delta: Option<Vec3>,
 }
 
@@ -46,7 +46,6 @@ impl Default for Moving {
             velocity: Vec3::X,
             speed: 0.0,
             last_delta: None,
-            route_history: Vec::new(),
             state: MovingState::Normal,
         }
     }
@@ -72,12 +71,15 @@ pub struct PhysicsPlugin;
 
 impl Plugin for PhysicsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_set(
-            SystemSet::on_update(GameState::InGame)
-                .with_system(collision_system)
-                .with_system(move_system)
-                .with_system(flip_sprite_system),
-        );
+        app.add_system_set_to_stage(
+            SimulationStage,
+            simulation_step(SimulationPhase::Move).with_system(move_system),
+        )
+        .add_system_set_to_stage(
+            SimulationStage,
+            simulation_step(SimulationPhase::Collide).with_system(collision_system),
+        )
+        .add_system_set(SystemSet::on_update(GameState::InGame).with_system(flip_sprite_system));
     }
 }
 
@@ -124,48 +126,15 @@ fn collision_system(
     }
 }
 
-fn move_system(mut query: Query<(&mut Moving, &mut Transform)>, time: Res<Time>) {
+fn move_system(
+    mut query: Query<(&mut Moving, &mut Transform)>,
+    simulation_time: Res<SimulationTime>,
+) {
     for (mut moving, mut transform) in &mut query {
-        match moving.state {
-            MovingState::Normal => {
-                let delta = moving.velocity.normalize() * moving.speed * time.delta_seconds();
-                moving.last_delta = Some(delta);
-                transform.translation += delta;
-
-                let indeces = get_indeces(transform.translation.truncate());
-                match moving.route_history.last() {
-                    Some(last_indeces) => {
-                        if *last_indeces != indeces {
-                            moving.route_history.push(indeces);
-                        }
-                    }
-                    None => moving.route_history.push(indeces),
-                }
-            }
-            MovingState::Retrace => {
-                // Remove last indeces from route history if we've arrived there.
-                if let Some(last_indeces) = moving.route_history.last() {
-                    let current_indeces = get_indeces(transform.translation.truncate());
-                    if current_indeces == *last_indeces {
-                        moving.route_history.pop();
-                    }
-                }
-
-                let delta = match moving.route_history.last() {
-                    Some((x, y)) => {
-                        let current_coordinates = get_coordinates(*x, *y);
-                        current_coordinates.extend(0.0) - transform.translation
-                    }
-                    None => Vec3::ZERO,
-                }
-                .normalize()
-                    * moving.speed
-                    * time.delta_seconds();
-
-                moving.last_delta = Some(delta);
-                transform.translation += delta;
-            }
-        }
+        let delta =
+            moving.velocity.normalize_or_zero() * moving.speed * simulation_time.delta_seconds();
+        moving.last_delta = Some(delta);
+        transform.translation += delta;
     }
 }
 
@@ -0,0 +1,126 @@
+//! Preview of the route co-workers would take if the selected item were placed at the cursor.
+//!
+//! The route is traced through a copy of the `VectorField` with the item added, so the real field
+//! is left alone until the item is actually placed. A warning is shown when the item would cut off
+//! every route to the tower, which most floors do not allow.
+//!
+
+use bevy::prelude::*;
+
+use crate::force::Placement;
+use crate::game::GameState;
+use crate::ghost::{Ghost, PlacementCursor};
+use crate::grid::{snap, CELL_SIZE};
+use crate::localization::Localization;
+use crate::pathfinding::VectorField;
+
+const DOT_SIZE: f32 = 6.0;
+const DOT_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.5);
+/// A dot is drawn on every this many cells of the route.
+const DOT_SPACING: usize = 2;
+const WARNING_COLOR: Color = Color::rgb(1.0, 0.3, 0.3);
+
+/// Dots along the predicted route, and the warning when there is none.
+#[derive(Component)]
+pub struct RoutePreview;
+
+/// Whether the item at the cursor would be rejected for cutting off the tower.
+#[derive(Default, Resource)]
+pub struct PredictedRoute {
+    pub sealed_off: bool,
+}
+
+pub struct RoutePlugin;
+
+impl Plugin for RoutePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PredictedRoute>()
+            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(preview_route))
+            .add_system_set(SystemSet::on_exit(GameState::InGame).with_system(cleanup_preview));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn preview_route(
+    mut commands: Commands,
+    cursor: Res<PlacementCursor>,
+    vector_field: Res<VectorField>,
+    placement: Placement,
+    mut predicted: ResMut<PredictedRoute>,
+    asset_server: Res<AssetServer>,
+    localization: Res<Localization>,
+    ghost_query: Query<(), With<Ghost>>,
+    preview_query: Query<Entity, With<RoutePreview>>,
+    mut previewed: Local<Option<Vec2>>,
+) {
+    let target = match ghost_query.is_empty() {
+        true => None,
+        false => Some(snap(cursor.position)),
+    };
+
+    // Tracing the route is expensive, so only do it when something changed.
+    if *previewed == target && !vector_field.is_changed() {
+        return;
+    }
+    *previewed = target;
+
+    for entity in &preview_query {
+        commands.entity(entity).despawn();
+    }
+
+    predicted.sealed_off = false;
+    let position = match target {
+        Some(position) => position,
+        None => return,
+    };
+
+    let routes = placement.routes(position);
+    predicted.sealed_off = placement.seals_off(&routes);
+
+    if routes.iter().any(Option::is_none) {
+        let warning = match predicted.sealed_off {
+            true => "route.sealed",
+            false => "route.blocked",
+        };
+
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    localization.get(warning),
+                    TextStyle {
+                        font: asset_server.load("fonts/PixeloidSans.ttf"),
+                        font_size: 20.0,
+                        color: WARNING_COLOR,
+                    },
+                )
+                .with_alignment(TextAlignment::CENTER),
+                transform: Transform::from_translation(
+                    (position - Vec2::new(0.0, CELL_SIZE / 2.0 + 12.0)).extend(1.0),
+                ),
+                ..default()
+            },
+            RoutePreview,
+        ));
+    }
+
+    for point in routes.iter().flatten().flatten().step_by(DOT_SPACING) {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: DOT_COLOR,
+                    custom_size: Some(Vec2::splat(DOT_SIZE)),
+                    ..default()
+                },
+                transform: Transform::from_translation(point.extend(0.5)),
+                ..default()
+            },
+            RoutePreview,
+        ));
+    }
+}
+
+fn cleanup_preview(mut commands: Commands, preview_query: Query<Entity, With<RoutePreview>>) {
+    for entity in &preview_query {
+        commands.entity(entity).despawn();
+    }
+}
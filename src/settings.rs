@@ -0,0 +1,416 @@
+//! Options of the player, which are kept between sessions.
+//!
+//! The settings are stored as RON in `settings.ron` next to the game, or in the local storage of
+//! the browser on the web. They can be changed on the settings screen of the main menu.
+//!
+
+use bevy::{prelude::*, window::WindowMode};
+use serde::{Deserialize, Serialize};
+
+use crate::camera::CameraShake;
+use crate::game::GameState;
+use crate::localization::{Language, Localization};
+use crate::simulation::{SimulationTime, GAME_SPEEDS};
+
+#[cfg(not(target_arch = "wasm32"))]
+const SETTINGS_PATH: &str = "settings.ron";
+#[cfg(target_arch = "wasm32")]
+const STORAGE_KEY: &str = "trumpet-trainee-settings";
+
+/// Intensities of the screen shake the player can choose from.
+const SCREEN_SHAKES: [f32; 5] = [0.0, 0.5, 1.0, 1.5, 2.0];
+
+/// Amount of steps between a volume of zero and one.
+const VOLUME_STEPS: f64 = 10.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sound {
+    Music,
+    Sfx,
+    Voice,
+}
+
+#[derive(Clone, Debug, PartialEq, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub master_volume: f64,
+    pub music_volume: f64,
+    pub sfx_volume: f64,
+    pub voice_volume: f64,
+    pub fullscreen: bool,
+    /// Multiplier for the screen shake, one of `SCREEN_SHAKES`.
+    pub screen_shake: f32,
+    /// Speed the game runs at, one of `GAME_SPEEDS`.
+    pub game_speed: f32,
+    pub language: Language,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            master_volume: 1.0,
+            music_volume: 0.5,
+            sfx_volume: 1.0,
+            voice_volume: 1.0,
+            fullscreen: false,
+            screen_shake: 1.0,
+            game_speed: 1.0,
+            language: Language::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// The volume to play a kind of sound at.
+    pub fn volume(&self, sound: Sound) -> f64 {
+        self.master_volume
+            * match sound {
+                Sound::Music => self.music_volume,
+                Sound::Sfx => self.sfx_volume,
+                Sound::Voice => self.voice_volume,
+            }
+    }
+
+    /// Read the settings of the last session, or use the defaults.
+    fn load() -> Self {
+        let contents = match read() {
+            Some(contents) => contents,
+            None => return Settings::default(),
+        };
+
+        ron::de::from_str(&contents).unwrap_or_else(|err| {
+            warn!("Could not read settings: {}", err);
+            Settings::default()
+        })
+    }
+
+    fn save(&self) {
+        match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(contents) => write(&contents),
+            Err(err) => warn!("Could not save settings: {}", err),
+        }
+    }
+
+    /// Change an option to its next value, wrapping around after the last one.
+    fn cycle(&mut self, option: SettingsOption) {
+        fn next_volume(volume: f64) -> f64 {
+            ((volume * VOLUME_STEPS).round() + 1.0) % (VOLUME_STEPS + 1.0) / VOLUME_STEPS
+        }
+
+        fn next_of(options: &[f32], current: f32) -> f32 {
+            let index = options
+                .iter()
+                .position(|option| *option == current)
+                .map_or(0, |index| (index + 1) % options.len());
+            options[index]
+        }
+
+        match option {
+            SettingsOption::MasterVolume => self.master_volume = next_volume(self.master_volume),
+            SettingsOption::MusicVolume => self.music_volume = next_volume(self.music_volume),
+            SettingsOption::SfxVolume => self.sfx_volume = next_volume(self.sfx_volume),
+            SettingsOption::VoiceVolume => self.voice_volume = next_volume(self.voice_volume),
+            SettingsOption::Fullscreen => self.fullscreen = !self.fullscreen,
+            SettingsOption::ScreenShake => {
+                self.screen_shake = next_of(&SCREEN_SHAKES, self.screen_shake)
+            }
+            SettingsOption::GameSpeed => self.game_speed = next_of(&GAME_SPEEDS, self.game_speed),
+            SettingsOption::Language => self.language = self.language.next(),
+        }
+    }
+
+    fn label(&self, option: SettingsOption, localization: &Localization) -> String {
+        fn percentage(value: f64) -> String {
+            format!("{}%", (value * 100.0).round())
+        }
+
+        let (key, value) = match option {
+            SettingsOption::MasterVolume => {
+                ("settings.master_volume", percentage(self.master_volume))
+            }
+            SettingsOption::MusicVolume => ("settings.music_volume", percentage(self.music_volume)),
+            SettingsOption::SfxVolume => ("settings.sfx_volume", percentage(self.sfx_volume)),
+            SettingsOption::VoiceVolume => ("settings.voice_volume", percentage(self.voice_volume)),
+            SettingsOption::Fullscreen => (
+                "settings.display",
+                localization
+                    .get(match self.fullscreen {
+                        true => "settings.display.fullscreen",
+                        false => "settings.display.windowed",
+                    })
+                    .to_string(),
+            ),
+            SettingsOption::ScreenShake => (
+                "settings.screen_shake",
+                percentage(self.screen_shake as f64),
+            ),
+            SettingsOption::GameSpeed => (
+                "settings.game_speed",
+                localization.format("speed.factor", &[("speed", &self.game_speed)]),
+            ),
+            SettingsOption::Language => (
+                "settings.language",
+                localization.get("language").to_string(),
+            ),
+        };
+
+        localization.format(key, &[("value", &value)])
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read() -> Option<String> {
+    std::fs::read_to_string(SETTINGS_PATH).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write(contents: &str) {
+    if let Err(err) = std::fs::write(SETTINGS_PATH, contents) {
+        warn!("Could not save settings: {}", err);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read() -> Option<String> {
+    let storage = web_sys::window()?.local_storage().ok()??;
+    storage.get_item(STORAGE_KEY).ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write(contents: &str) {
+    let storage = web_sys::window().and_then(|window| window.local_storage().ok().flatten());
+    if let Some(storage) = storage {
+        if storage.set_item(STORAGE_KEY, contents).is_err() {
+            warn!("Could not save settings");
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SettingsOption {
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    VoiceVolume,
+    Fullscreen,
+    ScreenShake,
+    GameSpeed,
+    Language,
+}
+
+impl SettingsOption {
+    const ALL: [SettingsOption; 8] = [
+        SettingsOption::MasterVolume,
+        SettingsOption::MusicVolume,
+        SettingsOption::SfxVolume,
+        SettingsOption::VoiceVolume,
+        SettingsOption::Fullscreen,
+        SettingsOption::ScreenShake,
+        SettingsOption::GameSpeed,
+        SettingsOption::Language,
+    ];
+}
+
+/// Opens the settings screen when clicked.
+#[derive(Component)]
+pub struct SettingsButton;
+
+/// Root of the settings screen.
+#[derive(Component)]
+pub struct SettingsMenu;
+
+#[derive(Component)]
+struct OptionButton(SettingsOption);
+
+#[derive(Component)]
+struct BackButton;
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Settings::load())
+            .add_system(apply_settings)
+            .add_system_set(
+                SystemSet::on_update(GameState::MainMenu)
+                    .with_system(open_settings)
+                    .with_system(click_option)
+                    .with_system(close_settings)
+                    .with_system(update_labels.after(click_option)),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::MainMenu).with_system(cleanup_settings));
+    }
+}
+
+/// Push changed settings to everything they affect, and save them for the next session.
+fn apply_settings(
+    settings: Res<Settings>,
+    mut localization: ResMut<Localization>,
+    mut simulation_time: ResMut<SimulationTime>,
+    mut windows: ResMut<Windows>,
+    mut camera_query: Query<&mut CameraShake>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    if localization.language() != settings.language {
+        localization.set_language(settings.language);
+    }
+
+    simulation_time.speed = settings.game_speed;
+
+    if let Some(window) = windows.get_primary_mut() {
+        window.set_mode(match settings.fullscreen {
+            true => WindowMode::BorderlessFullscreen,
+            false => WindowMode::Windowed,
+        });
+    }
+
+    for mut shake in &mut camera_query {
+        shake.set_intensity(settings.screen_shake);
+    }
+
+    if !settings.is_added() {
+        settings.save();
+    }
+}
+
+fn open_settings(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    localization: Res<Localization>,
+    asset_server: Res<AssetServer>,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<SettingsButton>)>,
+) {
+    if !interaction_query
+        .iter()
+        .any(|interaction| *interaction == Interaction::Clicked)
+    {
+        return;
+    }
+
+    fn create_button(
+        parent: &mut ChildBuilder,
+        text: String,
+        font: Handle<Font>,
+        component: impl Component,
+    ) {
+        parent
+            .spawn((
+                ButtonBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(600.0), Val::Auto),
+                        justify_content: JustifyContent::Center,
+                        padding: UiRect::all(Val::Px(10.0)),
+                        margin: UiRect::all(Val::Px(5.0)),
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.2, 0.2, 0.2).into(),
+                    ..default()
+                },
+                component,
+            ))
+            .with_children(|button| {
+                button.spawn(TextBundle::from_section(
+                    text,
+                    TextStyle {
+                        font,
+                        font_size: 30.0,
+                        color: Color::WHITE,
+                    },
+                ));
+            });
+    }
+
+    let font = asset_server.load("fonts/PixeloidSans.ttf");
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.9).into(),
+                ..default()
+            },
+            SettingsMenu,
+        ))
+        .with_children(|parent| {
+            for option in SettingsOption::ALL {
+                let text = settings.label(option, &localization);
+                create_button(parent, text, font.clone(), OptionButton(option));
+            }
+
+            let text = localization.get("settings.back").to_string();
+            create_button(parent, text, font.clone(), BackButton);
+        });
+}
+
+fn click_option(
+    mut settings: ResMut<Settings>,
+    interaction_query: Query<(&Interaction, &OptionButton), Changed<Interaction>>,
+) {
+    for (interaction, option_button) in &interaction_query {
+        if *interaction == Interaction::Clicked {
+            settings.cycle(option_button.0);
+        }
+    }
+}
+
+fn update_labels(
+    settings: Res<Settings>,
+    localization: Res<Localization>,
+    button_query: Query<(&OptionButton, &Children)>,
+    back_query: Query<&Children, With<BackButton>>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !settings.is_changed() && !localization.is_changed() {
+        return;
+    }
+
+    for (option_button, children) in &button_query {
+        for child in children {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                text.sections[0].value = settings.label(option_button.0, &localization);
+            }
+        }
+    }
+
+    for children in &back_query {
+        for child in children {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                text.sections[0].value = localization.get("settings.back").to_string();
+            }
+        }
+    }
+}
+
+fn close_settings(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    menu_query: Query<Entity, With<SettingsMenu>>,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<BackButton>)>,
+) {
+    let back = interaction_query
+        .iter()
+        .any(|interaction| *interaction == Interaction::Clicked);
+
+    if back || keys.just_pressed(KeyCode::Escape) {
+        for entity in &menu_query {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Close the settings screen together with the menu, so it never outlives it.
+fn cleanup_settings(mut commands: Commands, menu_query: Query<Entity, With<SettingsMenu>>) {
+    for entity in &menu_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,379 @@
+//! A fixed-timestep simulation for all gameplay systems.
+//!
+//! Every frame, the real time that has passed is added to an accumulator. The `SimulationStage` is
+//! then executed once for every `TIMESTEP` in that accumulator, so each step advances the game by
+//! exactly the same amount of time, independent of the frame rate.
+//!
+//! The player can pause or speed up the game, which scales the time added to the accumulator.
+//!
+//! Because the number of steps per frame varies, moving entities would stutter on screen. Entities
+//! with an `Interpolated` component are therefore rendered in between the positions of the last
+//! two steps, based on how much time is left in the accumulator.
+//!
+//! Gameplay systems run in the order of the `SimulationPhase`s, and those which need random
+//! numbers take them from `SimulationRng`, which is seeded at the start of every floor. Together
+//! with the fixed step, the same input then plays out the same way every time.
+//!
+
+use bevy::{ecs::schedule::ShouldRun, prelude::*, transform::TransformSystem};
+use rand::{rngs::StdRng, SeedableRng};
+use std::time::Duration;
+
+use crate::game::GameState;
+
+/// Duration of a single simulation step, in seconds.
+pub const TIMESTEP: f32 = 1.0 / 60.0;
+
+/// Upper bound for the amount of steps in a single frame at normal speed, so a slow frame can't
+/// snowball. Faster speeds allow proportionally more steps, so they aren't slowed down as well.
+const MAX_STEPS_PER_FRAME: u32 = 8;
+
+/// Seed of `SimulationRng` at the start of a floor.
+const SEED: u64 = 2022;
+
+/// Speeds the player can choose from.
+pub const GAME_SPEEDS: [f32; 3] = [1.0, 2.0, 4.0];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, StageLabel)]
+pub struct SimulationStage;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+pub enum SimulationLabel {
+    /// All gameplay systems of a single step.
+    Step,
+    /// Bookkeeping after each step.
+    Record,
+}
+
+/// Parts of a simulation step, which run in this order so every step plays out the same way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
+pub enum SimulationPhase {
+    /// Co-workers arrive on the floor.
+    Arrive,
+    /// Co-workers decide where to go.
+    Steer,
+    /// Everything which moves takes a step.
+    Move,
+    /// Steps into solid objects are undone.
+    Collide,
+    /// The outcome of the step: co-workers bump into the elephant or leave the floor, and the
+    /// money comes in.
+    React,
+}
+
+impl SimulationPhase {
+    fn previous(self) -> Option<SimulationPhase> {
+        match self {
+            SimulationPhase::Arrive => None,
+            SimulationPhase::Steer => Some(SimulationPhase::Arrive),
+            SimulationPhase::Move => Some(SimulationPhase::Steer),
+            SimulationPhase::Collide => Some(SimulationPhase::Move),
+            SimulationPhase::React => Some(SimulationPhase::Collide),
+        }
+    }
+}
+
+#[derive(Debug, Resource)]
+pub struct SimulationTime {
+    accumulator: Duration,
+    steps: u32,
+    scale: f32,
+    /// Multiplier for the speed of the game, one of `GAME_SPEEDS`.
+    pub speed: f32,
+    pub paused: bool,
+}
+
+impl Default for SimulationTime {
+    fn default() -> Self {
+        SimulationTime {
+            accumulator: Duration::ZERO,
+            steps: 0,
+            scale: 1.0,
+            speed: 1.0,
+            paused: false,
+        }
+    }
+}
+
+impl SimulationTime {
+    /// The time that passes during a single simulation step.
+    pub fn delta(&self) -> Duration {
+        Duration::from_secs_f32(TIMESTEP)
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        TIMESTEP
+    }
+
+    /// How much faster than real time the game currently runs; zero when paused.
+    ///
+    /// Always 1.0 outside of the game, so menus and cutscenes aren't affected.
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Switch to the next speed in `GAME_SPEEDS`, wrapping around to the first one.
+    pub fn next_speed(&mut self) {
+        let index = GAME_SPEEDS
+            .iter()
+            .position(|speed| *speed == self.speed)
+            .map_or(0, |index| (index + 1) % GAME_SPEEDS.len());
+        self.speed = GAME_SPEEDS[index];
+        self.paused = false;
+    }
+
+    /// How far the current frame is into the next simulation step, from 0.0 to 1.0.
+    fn overstep(&self) -> f32 {
+        (self.accumulator.as_secs_f32() / TIMESTEP).min(1.0)
+    }
+
+    /// Add the real time a frame took, scaled by the speed of the game when it is played.
+    fn accumulate(&mut self, delta: Duration, in_game: bool) {
+        self.steps = 0;
+
+        if in_game {
+            let scale = match self.paused {
+                true => 0.0,
+                false => self.speed,
+            };
+            self.scale = scale;
+            self.accumulator += delta.mul_f32(scale);
+        } else {
+            self.scale = 1.0;
+            self.accumulator = Duration::ZERO;
+            self.paused = false;
+        }
+    }
+
+    /// Upper bound for the amount of steps in a single frame at the current speed.
+    fn max_steps(&self) -> u32 {
+        (MAX_STEPS_PER_FRAME as f32 * self.speed.max(1.0)).ceil() as u32
+    }
+
+    /// Take the time of a step from the accumulator, if there is enough of it left this frame.
+    fn step(&mut self) -> bool {
+        let step = self.delta();
+
+        if self.steps >= self.max_steps() {
+            // Drop the time we couldn't catch up on instead of running even more steps next frame.
+            self.accumulator = self.accumulator.min(step);
+            false
+        } else if self.accumulator >= step {
+            self.accumulator -= step;
+            self.steps += 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Random numbers for gameplay systems, which run the same way every time the floor is played.
+#[derive(Resource, Deref, DerefMut)]
+pub struct SimulationRng(StdRng);
+
+impl Default for SimulationRng {
+    fn default() -> Self {
+        SimulationRng(StdRng::seed_from_u64(SEED))
+    }
+}
+
+/// Renders an entity in between its positions of the last two simulation steps.
+#[derive(Component)]
+pub struct Interpolated {
+    previous: Vec3,
+    current: Vec3,
+}
+
+impl Interpolated {
+    pub fn new(translation: Vec3) -> Self {
+        Interpolated {
+            previous: translation,
+            current: translation,
+        }
+    }
+}
+
+/// Creates a `SystemSet` for gameplay systems which should run once per simulation step, in the
+/// given phase of it.
+///
+/// The set has to be added to the `SimulationStage`.
+pub fn simulation_step(phase: SimulationPhase) -> SystemSet {
+    let set = SystemSet::new().label(SimulationLabel::Step).label(phase);
+
+    match phase.previous() {
+        Some(previous) => set.after(previous),
+        None => set,
+    }
+}
+
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SimulationTime::default())
+            .init_resource::<SimulationRng>()
+            .add_stage_after(
+                CoreStage::Update,
+                SimulationStage,
+                SystemStage::parallel().with_run_criteria(run_simulation_step),
+            )
+            .add_system_to_stage(CoreStage::PreUpdate, accumulate_time)
+            .add_system_to_stage(CoreStage::PreUpdate, restore_translation)
+            .add_system_set(SystemSet::on_enter(GameState::InGame).with_system(seed_rng))
+            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(keyboard_input))
+            .add_system_to_stage(
+                SimulationStage,
+                record_translation
+                    .label(SimulationLabel::Record)
+                    .after(SimulationLabel::Step),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                interpolate_translation.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+fn accumulate_time(
+    mut simulation_time: ResMut<SimulationTime>,
+    game_state: Res<State<GameState>>,
+    time: Res<Time>,
+) {
+    simulation_time.accumulate(time.delta(), *game_state.current() == GameState::InGame);
+}
+
+fn seed_rng(mut rng: ResMut<SimulationRng>) {
+    *rng = SimulationRng::default();
+}
+
+fn keyboard_input(keys: Res<Input<KeyCode>>, mut simulation_time: ResMut<SimulationTime>) {
+    if keys.just_pressed(KeyCode::Space) {
+        simulation_time.paused = !simulation_time.paused;
+    }
+
+    if keys.just_pressed(KeyCode::F) {
+        simulation_time.next_speed();
+    }
+}
+
+fn run_simulation_step(
+    mut simulation_time: ResMut<SimulationTime>,
+    game_state: Res<State<GameState>>,
+) -> ShouldRun {
+    if *game_state.current() == GameState::InGame && simulation_time.step() {
+        ShouldRun::YesAndCheckAgain
+    } else {
+        ShouldRun::No
+    }
+}
+
+/// Put interpolated entities back at their simulated position before anything reads it.
+fn restore_translation(mut query: Query<(&Interpolated, &mut Transform)>) {
+    for (interpolated, mut transform) in &mut query {
+        transform.translation = interpolated.current;
+    }
+}
+
+fn record_translation(mut query: Query<(&mut Interpolated, &Transform)>) {
+    for (mut interpolated, transform) in &mut query {
+        interpolated.previous = interpolated.current;
+        interpolated.current = transform.translation;
+    }
+}
+
+fn interpolate_translation(
+    simulation_time: Res<SimulationTime>,
+    mut query: Query<(&Interpolated, &mut Transform)>,
+) {
+    let overstep = simulation_time.overstep();
+
+    for (interpolated, mut transform) in &mut query {
+        transform.translation = interpolated.previous.lerp(interpolated.current, overstep);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Amount of steps run in a frame which took `seconds`.
+    fn run_frame(simulation_time: &mut SimulationTime, seconds: f32) -> u32 {
+        simulation_time.accumulate(Duration::from_secs_f32(seconds), true);
+        let mut steps = 0;
+        while simulation_time.step() {
+            steps += 1;
+        }
+        steps
+    }
+
+    #[test]
+    fn steps_do_not_depend_on_frame_rate() {
+        let mut fast = SimulationTime::default();
+        let mut slow = SimulationTime::default();
+
+        let fast_steps: u32 = (0..120).map(|_| run_frame(&mut fast, 1.0 / 120.0)).sum();
+        let slow_steps: u32 = (0..30).map(|_| run_frame(&mut slow, 1.0 / 30.0)).sum();
+
+        // A second has passed in both, part of which may still be left for the next step.
+        assert!((fast_steps as f32 + fast.overstep() - 60.0).abs() < 0.01);
+        assert!((slow_steps as f32 + slow.overstep() - 60.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn leftover_time_carries_over() {
+        let mut simulation_time = SimulationTime::default();
+
+        assert_eq!(run_frame(&mut simulation_time, TIMESTEP * 1.5), 1);
+        assert!((simulation_time.overstep() - 0.5).abs() < 0.01);
+        assert_eq!(run_frame(&mut simulation_time, TIMESTEP * 0.6), 1);
+    }
+
+    #[test]
+    fn speed_and_pause_scale_time() {
+        let mut simulation_time = SimulationTime {
+            speed: 2.0,
+            ..default()
+        };
+        assert_eq!(run_frame(&mut simulation_time, TIMESTEP * 2.25), 4);
+
+        simulation_time.paused = true;
+        assert_eq!(run_frame(&mut simulation_time, TIMESTEP * 2.25), 0);
+        assert_eq!(simulation_time.scale(), 0.0);
+    }
+
+    #[test]
+    fn slow_frames_are_capped() {
+        let mut simulation_time = SimulationTime::default();
+
+        assert_eq!(run_frame(&mut simulation_time, 1.0), MAX_STEPS_PER_FRAME);
+        assert!(simulation_time.accumulator <= simulation_time.delta());
+    }
+
+    #[test]
+    fn fast_speeds_keep_up_with_slow_frames() {
+        let mut simulation_time = SimulationTime {
+            speed: 4.0,
+            ..default()
+        };
+
+        // At 25 FPS, a frame takes 2.4 steps at normal speed.
+        let steps: u32 = (0..25).map(|_| run_frame(&mut simulation_time, 0.04)).sum();
+
+        assert!(steps >= 239);
+    }
+
+    #[test]
+    fn nothing_runs_outside_of_the_game() {
+        let mut simulation_time = SimulationTime {
+            paused: true,
+            ..default()
+        };
+
+        simulation_time.accumulate(Duration::from_secs(1), false);
+
+        assert!(!simulation_time.step());
+        assert!(!simulation_time.paused);
+        assert_eq!(simulation_time.scale(), 1.0);
+    }
+}
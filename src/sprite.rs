@@ -1,5 +1,7 @@
 use bevy::prelude::*;
 
+use crate::simulation::SimulationTime;
+
 #[derive(Component, Deref, DerefMut)]
 pub struct AnimationTimer(pub Timer);
 
@@ -13,6 +15,7 @@ impl Plugin for SpritePlugin {
 
 fn animate_sprites(
     time: Res<Time>,
+    simulation_time: Res<SimulationTime>,
     texture_atlases: Res<Assets<TextureAtlas>>,
     mut query: Query<(
         &mut AnimationTimer,
@@ -21,7 +24,7 @@ fn animate_sprites(
     )>,
 ) {
     for (mut timer, mut sprite, texture_atlas_handle) in &mut query {
-        timer.tick(time.delta());
+        timer.tick(time.delta().mul_f32(simulation_time.scale()));
         if timer.just_finished() {
             let texture_atlas = texture_atlases.get(texture_atlas_handle).unwrap();
             sprite.index = (sprite.index + 1) % texture_atlas.textures.len();
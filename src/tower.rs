@@ -1,24 +1,72 @@
-use bevy::prelude::*;
+//! The elephant co-workers must not find out about.
+//!
+//! Every tower has a suspicion meter, which fills while co-workers see the elephant (see `vision`)
+//! and while they bump into it, and slowly drains otherwise. Once it is full the elephant is
+//! discovered.
+//!
+
+use bevy::{prelude::*, sprite::Anchor};
 
 use crate::camera::CameraShake;
+use crate::enemies::Enemy;
 use crate::game::{GameState, Volatile};
-use crate::physics::{Collider, ColliderBundle, Solid};
+use crate::level::{Level, LoseCondition};
+use crate::physics::{Collider, ColliderBundle, Moving, MovingState, Solid};
+use crate::simulation::{simulation_step, SimulationPhase, SimulationStage, SimulationTime};
 use crate::sprite::AnimationTimer;
+use crate::vision::LineOfSight;
+
+/// Suspicion added per second while co-workers bump into the elephant. It is a rate rather than a
+/// fixed amount per bump, because co-workers are steered right back into the elephant and bump
+/// into it on almost every step.
+const TOUCH_SUSPICION: f32 = 0.4;
+/// Suspicion added per second for every co-worker who sees the elephant.
+const SIGHT_SUSPICION: f32 = 0.15;
+/// Suspicion drained per second while nobody sees the elephant.
+const SUSPICION_DRAIN: f32 = 0.05;
+
+/// Size of the suspicion meter, in pixels of the tower sprite.
+const METER_SIZE: Vec2 = Vec2::new(24.0, 2.0);
 
-#[allow(dead_code)] // The unused handles might come in handy later
 #[derive(Component, Default)]
 pub struct Tower {
     pub idle_handle: Handle<TextureAtlas>,
     pub scared_handle: Handle<TextureAtlas>,
     pub side_handle: Handle<TextureAtlas>,
+    /// How close co-workers are to finding out about the elephant, from 0.0 to 1.0.
+    pub suspicion: f32,
+    /// Whether the suspicion filled up. Co-workers only head for towers which are not discovered
+    /// yet.
+    pub discovered: bool,
 }
 
+impl Tower {
+    /// The elephant looks around nervously when co-workers get suspicious, and panics when they
+    /// almost found out.
+    fn atlas(&self) -> &Handle<TextureAtlas> {
+        match self.suspicion {
+            suspicion if suspicion < 1.0 / 3.0 => &self.idle_handle,
+            suspicion if suspicion < 2.0 / 3.0 => &self.side_handle,
+            _ => &self.scared_handle,
+        }
+    }
+}
+
+/// The filled part of the suspicion meter above a tower.
+#[derive(Component)]
+struct SuspicionMeter;
+
 pub struct TowerPlugin;
 
 impl Plugin for TowerPlugin {
     fn build(&self, app: &mut App) {
         app.add_system_set(SystemSet::on_enter(GameState::InGame).with_system(setup_tower))
-            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(hit_tower));
+            .add_system_set_to_stage(
+                SimulationStage,
+                simulation_step(SimulationPhase::React).with_system(update_suspicion),
+            )
+            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(show_suspicion))
+            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(discover_tower));
     }
 }
 
@@ -26,6 +74,7 @@ fn setup_tower(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    level: Res<Level>,
 ) {
     let idle_texture_handle = asset_server.load("sprites/spritesheet_elephant_front_idle.png");
     let idle_texture_atlas =
@@ -48,46 +97,143 @@ fn setup_tower(
         TextureAtlas::from_grid(side_texture_handle, Vec2::new(32.0, 32.0), 6, 1, None, None);
     let side_texture_atlas_handle = texture_atlases.add(side_texture_atlas);
 
-    commands.spawn((
-        SpriteSheetBundle {
-            texture_atlas: idle_texture_atlas_handle.clone(),
-            transform: Transform::from_scale(Vec3::splat(4.0))
-                .with_translation(Vec3::new(0.0, 0.0, -1.0)),
-            ..default()
-        },
-        AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)),
-        Tower {
-            idle_handle: idle_texture_atlas_handle,
-            scared_handle: scared_texture_atlas_handle,
-            side_handle: side_texture_atlas_handle,
-        },
-        ColliderBundle {
-            collider: Collider {
-                hit_box: Vec2::new(24.0 * 4.0, 24.0 * 4.0),
-                ..default()
-            },
-            ..default()
-        },
-        Solid,
-        Volatile,
-    ));
+    for position in level.towers() {
+        commands
+            .spawn((
+                SpriteSheetBundle {
+                    texture_atlas: idle_texture_atlas_handle.clone(),
+                    transform: Transform::from_scale(Vec3::splat(4.0))
+                        .with_translation(position.extend(-1.0)),
+                    ..default()
+                },
+                AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)),
+                Tower {
+                    idle_handle: idle_texture_atlas_handle.clone(),
+                    scared_handle: scared_texture_atlas_handle.clone(),
+                    side_handle: side_texture_atlas_handle.clone(),
+                    ..default()
+                },
+                ColliderBundle {
+                    collider: Collider {
+                        hit_box: Vec2::new(24.0 * 4.0, 24.0 * 4.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                Solid,
+                Volatile,
+            ))
+            .with_children(|parent| {
+                let above = Vec3::new(0.0, 16.0, 0.1);
+
+                parent.spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::rgba(0.0, 0.0, 0.0, 0.5),
+                        custom_size: Some(METER_SIZE),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(above),
+                    ..default()
+                });
+                parent.spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            custom_size: Some(METER_SIZE),
+                            anchor: Anchor::CenterLeft,
+                            ..default()
+                        },
+                        transform: Transform::from_translation(
+                            above + Vec3::new(-METER_SIZE.x / 2.0, 0.0, 0.1),
+                        ),
+                        ..default()
+                    },
+                    SuspicionMeter,
+                ));
+            });
+    }
 }
 
-fn hit_tower(
-    mut game_state: ResMut<State<GameState>>,
-    mut tower_query: Query<(&Tower, &mut Handle<TextureAtlas>, &Collider)>,
-    mut camera_query: Query<&mut CameraShake, Without<Tower>>,
+fn update_suspicion(
+    mut tower_query: Query<(&mut Tower, &mut Collider, &Transform)>,
+    enemy_query: Query<(&Transform, &Moving), With<Enemy>>,
+    line_of_sight: LineOfSight,
+    simulation_time: Res<SimulationTime>,
+) {
+    let delta = simulation_time.delta_seconds();
+
+    for (mut tower, mut collider, transform) in &mut tower_query {
+        if tower.discovered {
+            continue;
+        }
+
+        // The hit flag of a solid stays set, so reset it to notice the next bump.
+        let touched = collider.hit;
+        collider.hit = false;
+
+        let tower_position = transform.translation.truncate();
+        let watchers = enemy_query
+            .iter()
+            .filter(|(_, moving)| moving.state == MovingState::Normal)
+            .filter(|(enemy, moving)| {
+                let position = enemy.translation.truncate();
+                line_of_sight.sees(position, moving.velocity.truncate(), tower_position)
+            })
+            .count();
+
+        let mut suspicion = tower.suspicion + watchers as f32 * SIGHT_SUSPICION * delta;
+        if touched {
+            suspicion += TOUCH_SUSPICION * delta;
+        }
+        if watchers == 0 && !touched {
+            suspicion -= SUSPICION_DRAIN * delta;
+        }
+
+        tower.suspicion = suspicion.clamp(0.0, 1.0);
+        tower.discovered = tower.suspicion >= 1.0;
+    }
+}
+
+fn show_suspicion(
+    mut tower_query: Query<(&Tower, &mut Handle<TextureAtlas>, &Children), Changed<Tower>>,
+    mut meter_query: Query<(&mut Transform, &mut Sprite), With<SuspicionMeter>>,
 ) {
-    let (tower, mut atlas, tower_collider) = tower_query.single_mut();
+    for (tower, mut atlas, children) in &mut tower_query {
+        if *atlas != *tower.atlas() {
+            *atlas = tower.atlas().clone();
+        }
 
-    if !tower_collider.hit {
-        return;
+        for child in children {
+            if let Ok((mut transform, mut sprite)) = meter_query.get_mut(*child) {
+                transform.scale.x = tower.suspicion;
+                // From yellow to red as the suspicion grows.
+                sprite.color = Color::rgb(1.0, 1.0 - tower.suspicion, 0.0);
+            }
+        }
     }
+}
 
-    let mut shake = camera_query.single_mut();
-    shake.trauma += 0.7;
+/// Lose the floor once the elephant is discovered, at one or all towers depending on the level.
+pub fn discover_tower(
+    mut game_state: ResMut<State<GameState>>,
+    level: Res<Level>,
+    tower_query: Query<&Tower>,
+    mut camera_query: Query<&mut CameraShake, Without<Tower>>,
+    mut discovered_before: Local<usize>,
+) {
+    let discovered = tower_query.iter().filter(|tower| tower.discovered).count();
+    if discovered > *discovered_before {
+        let mut shake = camera_query.single_mut();
+        shake.trauma += 0.7;
+    }
+    *discovered_before = discovered;
 
-    game_state.set(GameState::GameOver).unwrap();
+    let lost = match level.lose_condition() {
+        LoseCondition::AnyDiscovered => discovered > 0,
+        LoseCondition::AllDiscovered => discovered > 0 && discovered == tower_query.iter().len(),
+    };
 
-    *atlas = tower.scared_handle.clone();
+    if lost {
+        // Fails when another state has been queued this frame already, e.g. to stop a test-play.
+        let _ = game_state.set(GameState::GameOver);
+    }
 }
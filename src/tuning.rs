@@ -0,0 +1,241 @@
+//! Values to balance the game with, read from `assets/game.tuning.ron`.
+//!
+//! The file is watched when the game runs natively, so changes are applied as soon as it is saved,
+//! also to items which have already been placed. Values missing from the file keep their defaults.
+//!
+//! The economy of a floor can differ from the default one, by listing it under its number in
+//! `floors`. Level files can have an economy of their own as well.
+//!
+
+use bevy::{
+    asset::{AssetLoader, BoxedFuture, Error, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::HashMap,
+};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::level::Level;
+
+const TUNING_ASSET: &str = "game.tuning.ron";
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct Prices {
+    pub passive: f32,
+    pub attract: f32,
+    pub repel: f32,
+}
+
+impl Default for Prices {
+    fn default() -> Self {
+        Prices {
+            passive: 30.0,
+            attract: 50.0,
+            repel: 45.0,
+        }
+    }
+}
+
+/// How players earn money on a floor.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Economy {
+    /// Money at the start of the floor.
+    pub starting_money: f32,
+    /// Money earned per second, as (seconds since the start of the floor, rate) points. The rate
+    /// changes gradually between the points, and stays the same before the first and after the
+    /// last. The points may be listed in any order.
+    #[serde(deserialize_with = "sorted_income")]
+    pub income: Vec<(f32, f32)>,
+    /// Money for every co-worker who gives up and leaves the floor.
+    pub bounty: f32,
+    /// Share of the unspent money which is added to it every `interest_interval`.
+    pub interest: f32,
+    /// Seconds between payments of interest.
+    pub interest_interval: f32,
+}
+
+impl Default for Economy {
+    fn default() -> Self {
+        Economy {
+            starting_money: 30.0,
+            income: vec![(0.0, 5.0)],
+            bounty: 0.0,
+            interest: 0.0,
+            interest_interval: 10.0,
+        }
+    }
+}
+
+/// Read the points of an income curve in order of time.
+fn sorted_income<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<(f32, f32)>, D::Error> {
+    let mut income = Vec::<(f32, f32)>::deserialize(deserializer)?;
+    income.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    Ok(income)
+}
+
+impl Economy {
+    /// Money earned per second at a time since the start of the floor.
+    pub fn income_rate(&self, seconds: f32) -> f32 {
+        let next = self.income.iter().position(|(time, _)| *time > seconds);
+
+        match next {
+            None => self.income.last().map_or(0.0, |(_, rate)| *rate),
+            Some(0) => self.income[0].1,
+            Some(index) => {
+                let (start, from) = self.income[index - 1];
+                let (end, to) = self.income[index];
+                from + (to - from) * (seconds - start) / (end - start)
+            }
+        }
+    }
+}
+
+/// The values in use. Until the file has been read, these are the defaults.
+#[derive(Clone, Debug, Deserialize, Resource, TypeUuid)]
+#[uuid = "3f6e2a9d-81c4-4b7a-b0d5-6c2e9f14a8b3"]
+#[serde(default)]
+pub struct Tuning {
+    /// Strength with which attracting and repelling items push or pull co-workers.
+    pub newton: f32,
+    /// Distance within which attracting and repelling items affect co-workers, in sprite pixels.
+    pub influence: f32,
+    pub prices: Prices,
+    /// Economy of floors which don't have one of their own.
+    pub economy: Economy,
+    /// Economies of floors, by their number.
+    pub floors: HashMap<u8, Economy>,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Tuning {
+            newton: 500.0,
+            influence: 50.0,
+            prices: Prices::default(),
+            economy: Economy::default(),
+            floors: HashMap::default(),
+        }
+    }
+}
+
+impl Tuning {
+    /// The economy of the floor which is played.
+    pub fn economy<'a>(&'a self, level: &'a Level) -> &'a Economy {
+        level
+            .layout
+            .economy
+            .as_ref()
+            .or_else(|| self.floors.get(&level.level))
+            .unwrap_or(&self.economy)
+    }
+}
+
+#[derive(Default)]
+struct TuningLoader;
+
+impl AssetLoader for TuningLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let tuning: Tuning = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(tuning));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tuning.ron"]
+    }
+}
+
+/// Keeps the tuning file loaded, so it is reloaded when it changes.
+#[derive(Resource)]
+struct TuningHandle(Handle<Tuning>);
+
+pub struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tuning>()
+            .add_asset::<Tuning>()
+            .init_asset_loader::<TuningLoader>()
+            .add_startup_system(load_tuning)
+            .add_system(apply_tuning);
+    }
+}
+
+fn load_tuning(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(TuningHandle(asset_server.load(TUNING_ASSET)));
+}
+
+fn apply_tuning(
+    mut ev_asset: EventReader<AssetEvent<Tuning>>,
+    handle: Res<TuningHandle>,
+    assets: Res<Assets<Tuning>>,
+    mut tuning: ResMut<Tuning>,
+) {
+    for ev in ev_asset.iter() {
+        match ev {
+            AssetEvent::Created { handle: changed } | AssetEvent::Modified { handle: changed }
+                if *changed == handle.0 =>
+            {
+                if let Some(loaded) = assets.get(changed) {
+                    info!("Applied {}", TUNING_ASSET);
+                    *tuning = loaded.clone();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn economy(income: &str) -> Economy {
+        ron::de::from_str(&format!("(income: {})", income)).unwrap()
+    }
+
+    #[test]
+    fn income_is_flat_before_the_first_point() {
+        let economy = economy("[(10.0, 5.0), (20.0, 8.0)]");
+
+        assert_eq!(economy.income_rate(0.0), 5.0);
+        assert_eq!(economy.income_rate(10.0), 5.0);
+    }
+
+    #[test]
+    fn income_changes_gradually_between_points() {
+        let economy = economy("[(10.0, 5.0), (20.0, 8.0)]");
+
+        assert_eq!(economy.income_rate(15.0), 6.5);
+        assert_eq!(economy.income_rate(17.5), 7.25);
+    }
+
+    #[test]
+    fn income_is_flat_after_the_last_point() {
+        let economy = economy("[(10.0, 5.0), (20.0, 8.0)]");
+
+        assert_eq!(economy.income_rate(20.0), 8.0);
+        assert_eq!(economy.income_rate(100.0), 8.0);
+    }
+
+    #[test]
+    fn income_points_are_sorted_when_read() {
+        let economy = economy("[(20.0, 8.0), (0.0, 2.0), (10.0, 5.0)]");
+
+        assert_eq!(economy.income, vec![(0.0, 2.0), (10.0, 5.0), (20.0, 8.0)]);
+        assert_eq!(economy.income_rate(15.0), 6.5);
+    }
+
+    #[test]
+    fn no_income_without_points() {
+        assert_eq!(economy("[]").income_rate(5.0), 0.0);
+    }
+}
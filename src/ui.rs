@@ -1,16 +1,29 @@
 use bevy::prelude::*;
 use rand::Rng;
 
+use crate::camera::PLAY_AREA;
+use crate::controls::Controls;
 use crate::cost::Points;
 use crate::force::ForceType;
 use crate::game::GameState;
 use crate::ghost::Ghost;
+use crate::localization::Localization;
+use crate::simulation::{SimulationTime, GAME_SPEEDS};
+use crate::tuning::Tuning;
+
+/// Width of the bar on the left of the play area.
+pub const BAR_WIDTH: f32 = PLAY_AREA.x / 12.0;
+const SPEED_BAR_HEIGHT: f32 = 40.0;
 
 #[derive(Component, Default)]
 pub struct UIBar {
     pub selected_force: Option<ForceType>,
 }
 
+/// Text of an item button, which shows the price.
+#[derive(Component)]
+struct PriceLabel(ForceType);
+
 #[derive(Component)]
 struct ForceButton {
     force_type: ForceType,
@@ -39,21 +52,65 @@ impl ForceButton {
     }
 }
 
+/// Sets the game speed; a speed of zero pauses the game.
+#[derive(Component)]
+struct SpeedButton {
+    speed: f32,
+    hovered: bool,
+}
+
+impl SpeedButton {
+    fn selected(&self, simulation_time: &SimulationTime) -> bool {
+        match self.speed == 0.0 {
+            true => simulation_time.paused,
+            false => !simulation_time.paused && self.speed == simulation_time.speed,
+        }
+    }
+
+    fn label(&self, localization: &Localization) -> String {
+        match self.speed == 0.0 {
+            true => localization.get("speed.pause").to_string(),
+            false => localization.format("speed.factor", &[("speed", &self.speed)]),
+        }
+    }
+}
+
 pub struct UIPlugin;
 
 impl Plugin for UIPlugin {
     fn build(&self, app: &mut App) {
         app.add_system_set(SystemSet::on_enter(GameState::InGame).with_system(setup))
             .add_system_set(SystemSet::on_update(GameState::InGame).with_system(click_button))
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame).with_system(select_with_controls),
+            )
             .add_system_set(SystemSet::on_update(GameState::InGame).with_system(button_color))
+            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(update_prices))
+            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(click_speed_button))
+            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(speed_button_color))
             .add_system_set(SystemSet::on_exit(GameState::InGame).with_system(cleanup_uibar));
     }
 }
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>, windows: Res<Windows>) {
-    let window = windows.primary();
-    let bar_width = window.width() / 12.0;
-    let bar_height = window.height();
+/// Name and price of an item in the UI bar.
+fn price_label(force_type: ForceType, localization: &Localization, tuning: &Tuning) -> String {
+    let key = match force_type {
+        ForceType::Passive => "force.passive",
+        ForceType::Attract => "force.attract",
+        ForceType::Repel => "force.repel",
+    };
+    localization.format(key, &[("price", &force_type.price(tuning))])
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    localization: Res<Localization>,
+    tuning: Res<Tuning>,
+) {
+    let bar_width = BAR_WIDTH;
+    let bar_height = PLAY_AREA.y;
+    let button_height = (bar_height - SPEED_BAR_HEIGHT) / 3.0;
 
     fn create_ui_button(width: f32, height: f32, background_color: Color) -> ButtonBundle {
         ButtonBundle {
@@ -72,31 +129,26 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>, windows: Res<Wi
         .spawn((
             NodeBundle {
                 style: Style {
-                    size: Size::new(Val::Px(bar_width), Val::Percent(bar_height)),
+                    size: Size::new(Val::Px(bar_width), Val::Px(bar_height)),
                     flex_direction: FlexDirection::Column,
                     ..default()
                 },
-                transform: Transform::from_translation(Vec3::new(
-                    (window.width() - bar_width) / 2.0,
-                    0.0,
-                    0.0,
-                )),
                 ..default()
             },
             UIBar::default(),
         ))
         .with_children(|bar| {
             bar.spawn((
-                create_ui_button(bar_width, bar_height / 3.0, ForceType::Passive.into()),
+                create_ui_button(bar_width, button_height, ForceType::Passive.into()),
                 ForceButton {
                     force_type: ForceType::Passive,
                     hovered: false,
                 },
             ))
             .with_children(|parent| {
-                parent.spawn(
+                parent.spawn((
                     TextBundle::from_section(
-                        "Box\nPassive\n$30",
+                        price_label(ForceType::Passive, &localization, &tuning),
                         TextStyle {
                             font: asset_server.load("fonts/PixeloidSans.ttf"),
                             font_size: 20.0,
@@ -104,20 +156,21 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>, windows: Res<Wi
                         },
                     )
                     .with_text_alignment(TextAlignment::TOP_CENTER),
-                );
+                    PriceLabel(ForceType::Passive),
+                ));
             });
 
             bar.spawn((
-                create_ui_button(bar_width, bar_height / 3.0, ForceType::Attract.into()),
+                create_ui_button(bar_width, button_height, ForceType::Attract.into()),
                 ForceButton {
                     force_type: ForceType::Attract,
                     hovered: false,
                 },
             ))
             .with_children(|parent| {
-                parent.spawn(
+                parent.spawn((
                     TextBundle::from_section(
-                        "Coffee\nAttract\n$50",
+                        price_label(ForceType::Attract, &localization, &tuning),
                         TextStyle {
                             font: asset_server.load("fonts/PixeloidSans.ttf"),
                             font_size: 20.0,
@@ -125,20 +178,21 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>, windows: Res<Wi
                         },
                     )
                     .with_text_alignment(TextAlignment::TOP_CENTER),
-                );
+                    PriceLabel(ForceType::Attract),
+                ));
             });
 
             bar.spawn((
-                create_ui_button(bar_width, bar_height / 3.0, ForceType::Repel.into()),
+                create_ui_button(bar_width, button_height, ForceType::Repel.into()),
                 ForceButton {
                     force_type: ForceType::Repel,
                     hovered: false,
                 },
             ))
             .with_children(|parent| {
-                parent.spawn(
+                parent.spawn((
                     TextBundle::from_section(
-                        "Work\nRepel\n$45",
+                        price_label(ForceType::Repel, &localization, &tuning),
                         TextStyle {
                             font: asset_server.load("fonts/PixeloidSans.ttf"),
                             font_size: 20.0,
@@ -146,16 +200,111 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>, windows: Res<Wi
                         },
                     )
                     .with_text_alignment(TextAlignment::TOP_CENTER),
-                );
+                    PriceLabel(ForceType::Repel),
+                ));
+            });
+
+            bar.spawn(NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Px(bar_width), Val::Px(SPEED_BAR_HEIGHT)),
+                    flex_direction: FlexDirection::Row,
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|row| {
+                let speeds = [0.0].iter().chain(GAME_SPEEDS.iter());
+                let button_width = bar_width / (GAME_SPEEDS.len() + 1) as f32;
+
+                for speed in speeds {
+                    let speed_button = SpeedButton {
+                        speed: *speed,
+                        hovered: false,
+                    };
+                    let label = speed_button.label(&localization);
+
+                    row.spawn((
+                        create_ui_button(button_width, SPEED_BAR_HEIGHT, Color::NONE),
+                        speed_button,
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            label,
+                            TextStyle {
+                                font: asset_server.load("fonts/PixeloidSans.ttf"),
+                                font_size: 16.0,
+                                color: Color::rgba(0.9, 0.9, 0.9, 0.7),
+                            },
+                        ));
+                    });
+                }
             });
         });
 }
 
+/// Select an item to place and show its ghost at the cursor, or deselect it when it already was.
+fn select_force(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    texture_atlases: &mut Assets<TextureAtlas>,
+    uibar: &mut UIBar,
+    ghost_query: &Query<Entity, With<Ghost>>,
+    force_type: ForceType,
+) {
+    let mut rng = rand::thread_rng();
+
+    for entity in ghost_query {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    if uibar.selected_force == Some(force_type) {
+        uibar.selected_force = None;
+        return;
+    }
+    uibar.selected_force = Some(force_type);
+
+    let texture_atlas = match force_type {
+        ForceType::Passive => {
+            let render_box_a: bool = rng.gen();
+            let texture_handle = asset_server.load(match render_box_a {
+                true => "sprites/BoxA.png",
+                false => "sprites/BoxB.png",
+            });
+            TextureAtlas::from_grid(texture_handle, Vec2::new(16.0, 16.0), 1, 1, None, None)
+        }
+        ForceType::Attract => {
+            let texture_handle = asset_server.load("sprites/spritesheet_coffee.png");
+            TextureAtlas::from_grid(texture_handle, Vec2::new(16.0, 16.0), 6, 1, None, None)
+        }
+        ForceType::Repel => {
+            let render_box_a: bool = rng.gen();
+            let texture_handle = asset_server.load(match render_box_a {
+                true => "sprites/Stack_of_work.png",
+                false => "sprites/Stack_of_work_B.png",
+            });
+            TextureAtlas::from_grid(texture_handle, Vec2::new(16.0, 16.0), 1, 1, None, None)
+        }
+    };
+    let texture_atlas_handle = texture_atlases.add(texture_atlas);
+
+    let sprite_sheet_bundle = SpriteSheetBundle {
+        texture_atlas: texture_atlas_handle,
+        transform: Transform {
+            scale: Vec3::splat(4.0 * 1.5),
+            ..default()
+        },
+        ..default()
+    };
+    commands.spawn((sprite_sheet_bundle, Ghost { force_type }));
+}
+
+#[allow(clippy::too_many_arguments)]
 fn click_button(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
     points: Res<Points>,
+    tuning: Res<Tuning>,
     mut interaction_query: Query<
         (&mut ForceButton, &Interaction),
         (Changed<Interaction>, With<Button>),
@@ -164,77 +313,19 @@ fn click_button(
     ghost_query: Query<Entity, With<Ghost>>,
 ) {
     let mut uibar = uibar_query.single_mut();
-    let mut rng = rand::thread_rng();
 
     for (mut force_button, interaction) in &mut interaction_query {
         match *interaction {
             Interaction::Clicked => {
-                if force_button.force_type.price() <= points.owned {
-                    if uibar.selected_force == Some(force_button.force_type) {
-                        uibar.selected_force = None;
-                    } else {
-                        uibar.selected_force = Some(force_button.force_type);
-                    }
-
-                    for entity in &ghost_query {
-                        commands.entity(entity).despawn();
-                    }
-                    let texture_atlas = match force_button.force_type {
-                        ForceType::Passive => {
-                            let render_box_a: bool = rng.gen();
-                            let texture_handle = asset_server.load(match render_box_a {
-                                true => "sprites/BoxA.png",
-                                false => "sprites/BoxB.png",
-                            });
-                            TextureAtlas::from_grid(
-                                texture_handle,
-                                Vec2::new(16.0, 16.0),
-                                1,
-                                1,
-                                None,
-                                None,
-                            )
-                        }
-                        ForceType::Attract => {
-                            let texture_handle =
-                                asset_server.load("sprites/spritesheet_coffee.png");
-                            TextureAtlas::from_grid(
-                                texture_handle,
-                                Vec2::new(16.0, 16.0),
-                                6,
-                                1,
-                                None,
-                                None,
-                            )
-                        }
-                        ForceType::Repel => {
-                            let render_box_a: bool = rng.gen();
-                            let texture_handle = asset_server.load(match render_box_a {
-                                true => "sprites/Stack_of_work.png",
-                                false => "sprites/Stack_of_work_B.png",
-                            });
-                            TextureAtlas::from_grid(
-                                texture_handle,
-                                Vec2::new(16.0, 16.0),
-                                1,
-                                1,
-                                None,
-                                None,
-                            )
-                        }
-                    };
-                    let texture_atlas_handle = texture_atlases.add(texture_atlas);
-
-                    let mut sprite_sheet_bundle = SpriteSheetBundle {
-                        texture_atlas: texture_atlas_handle,
-                        transform: Transform {
-                            scale: Vec3::splat(4.0 * 1.5),
-                            ..default()
-                        },
-                        ..default()
-                    };
-                    sprite_sheet_bundle.sprite.color.set_a(0.2);
-                    commands.spawn((sprite_sheet_bundle, Ghost));
+                if force_button.force_type.price(&tuning) <= points.owned {
+                    select_force(
+                        &mut commands,
+                        &asset_server,
+                        &mut texture_atlases,
+                        &mut uibar,
+                        &ghost_query,
+                        force_button.force_type,
+                    );
                 }
             }
             Interaction::Hovered => {
@@ -247,15 +338,70 @@ fn click_button(
     }
 }
 
+/// Select items with the number keys or by cycling through the affordable ones, and deselect them
+/// with cancel.
+#[allow(clippy::too_many_arguments)]
+fn select_with_controls(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    points: Res<Points>,
+    tuning: Res<Tuning>,
+    controls: Controls,
+    mut uibar_query: Query<&mut UIBar>,
+    ghost_query: Query<Entity, With<Ghost>>,
+) {
+    let mut uibar = uibar_query.single_mut();
+
+    if controls.cancel() {
+        uibar.selected_force = None;
+        for entity in &ghost_query {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+
+    let affordable = |force_type: &ForceType| force_type.price(&tuning) <= points.owned;
+
+    let force_type = match (controls.selected_item(), controls.cycle_item()) {
+        (Some(index), _) => ForceType::ALL.get(index).copied().filter(affordable),
+        (None, 0) => None,
+        (None, step) => {
+            let count = ForceType::ALL.len() as i32;
+            let current = uibar
+                .selected_force
+                .and_then(|selected| ForceType::ALL.iter().position(|t| *t == selected))
+                .map_or(if step > 0 { -1 } else { count }, |index| index as i32);
+
+            (1..=count)
+                .map(|offset| ForceType::ALL[(current + step * offset).rem_euclid(count) as usize])
+                .find(affordable)
+                .filter(|force_type| uibar.selected_force != Some(*force_type))
+        }
+    };
+
+    if let Some(force_type) = force_type {
+        select_force(
+            &mut commands,
+            &asset_server,
+            &mut texture_atlases,
+            &mut uibar,
+            &ghost_query,
+            force_type,
+        );
+    }
+}
+
 fn button_color(
     points: Res<Points>,
+    tuning: Res<Tuning>,
     mut button_query: Query<(&ForceButton, &mut BackgroundColor)>,
     mut uibar_query: Query<&mut UIBar>,
 ) {
     let mut uibar = uibar_query.single_mut();
 
     for (force_button, mut color) in &mut button_query {
-        if force_button.force_type.price() <= points.owned {
+        if force_button.force_type.price(&tuning) <= points.owned {
             *color = force_button.color(&mut uibar).into();
         } else {
             *color = Color::rgba(1.0, 1.0, 1.0, 0.1).into();
@@ -263,6 +409,62 @@ fn button_color(
     }
 }
 
+fn update_prices(
+    tuning: Res<Tuning>,
+    localization: Res<Localization>,
+    mut label_query: Query<(&PriceLabel, &mut Text)>,
+) {
+    if !tuning.is_changed() {
+        return;
+    }
+
+    for (label, mut text) in &mut label_query {
+        text.sections[0].value = price_label(label.0, &localization, &tuning);
+    }
+}
+
+fn click_speed_button(
+    mut simulation_time: ResMut<SimulationTime>,
+    mut interaction_query: Query<
+        (&mut SpeedButton, &Interaction),
+        (Changed<Interaction>, With<Button>),
+    >,
+) {
+    for (mut speed_button, interaction) in &mut interaction_query {
+        match *interaction {
+            Interaction::Clicked => {
+                if speed_button.speed == 0.0 {
+                    simulation_time.paused = !simulation_time.paused;
+                } else {
+                    simulation_time.speed = speed_button.speed;
+                    simulation_time.paused = false;
+                }
+            }
+            Interaction::Hovered => {
+                speed_button.hovered = true;
+            }
+            Interaction::None => {
+                speed_button.hovered = false;
+            }
+        }
+    }
+}
+
+fn speed_button_color(
+    simulation_time: Res<SimulationTime>,
+    mut button_query: Query<(&SpeedButton, &mut BackgroundColor)>,
+) {
+    for (speed_button, mut color) in &mut button_query {
+        *color = if speed_button.selected(&simulation_time) {
+            Color::rgba(1.0, 1.0, 1.0, 0.4).into()
+        } else if speed_button.hovered {
+            Color::rgba(1.0, 1.0, 1.0, 0.2).into()
+        } else {
+            Color::rgba(1.0, 1.0, 1.0, 0.05).into()
+        };
+    }
+}
+
 fn cleanup_uibar(mut commands: Commands, uibar_query: Query<Entity, With<UIBar>>) {
     let uibar = uibar_query.single();
     commands.entity(uibar).despawn_recursive();
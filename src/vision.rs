@@ -0,0 +1,294 @@
+//! What co-workers can see.
+//!
+//! Co-workers look in the direction they walk, within a cone of `VISION_RANGE` long and
+//! `VISION_ANGLE` wide. Solid objects like cubicle screens, cabinets and placed items block their
+//! line of sight, so props can hide the elephant.
+//!
+//! Press F3 to show the vision cones, red for co-workers who see an elephant.
+//!
+
+use bevy::{
+    ecs::system::SystemParam,
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+    sprite::MaterialMesh2dBundle,
+};
+
+use crate::enemies::Enemy;
+use crate::game::GameState;
+use crate::physics::{Collider, Moving, MovingState, Solid};
+use crate::tower::Tower;
+
+/// How far co-workers can see.
+const VISION_RANGE: f32 = 250.0;
+/// Angle between the edges of the vision cone, in radians.
+const VISION_ANGLE: f32 = std::f32::consts::FRAC_PI_2;
+/// Amount of triangles the debug rendering of a vision cone is made of.
+const CONE_SEGMENTS: usize = 12;
+
+/// Checks whether co-workers can see something.
+#[derive(SystemParam)]
+pub struct LineOfSight<'w, 's> {
+    solid_query:
+        Query<'w, 's, (&'static Collider, &'static Transform), (With<Solid>, Without<Tower>)>,
+}
+
+impl<'w, 's> LineOfSight<'w, 's> {
+    /// Whether a co-worker at a position, looking in a direction, sees the target.
+    pub fn sees(&self, position: Vec2, facing: Vec2, target: Vec2) -> bool {
+        in_cone(position, facing, target)
+            && !self.solid_query.iter().any(|(collider, transform)| {
+                let center = transform.translation.truncate();
+                // Solids which contain the co-worker, like the one they bumped into, don't count.
+                !contains(center, collider.hit_box, position)
+                    && intersects(position, target, center, collider.hit_box)
+            })
+    }
+}
+
+fn in_cone(position: Vec2, facing: Vec2, target: Vec2) -> bool {
+    let offset = target - position;
+
+    offset.length() < VISION_RANGE
+        && facing != Vec2::ZERO
+        && facing.angle_between(offset).abs() < VISION_ANGLE / 2.0
+}
+
+fn contains(center: Vec2, size: Vec2, point: Vec2) -> bool {
+    let half = size / 2.0;
+    (point - center).abs().cmplt(half).all()
+}
+
+/// Whether the line segment from `from` to `to` crosses the box, using the slab method.
+fn intersects(from: Vec2, to: Vec2, center: Vec2, size: Vec2) -> bool {
+    let direction = to - from;
+    let min = center - size / 2.0;
+    let max = center + size / 2.0;
+
+    let (mut enter, mut exit) = (0.0_f32, 1.0_f32);
+    for axis in 0..2 {
+        if direction[axis] == 0.0 {
+            if from[axis] < min[axis] || from[axis] > max[axis] {
+                return false;
+            }
+            continue;
+        }
+
+        let near = (min[axis] - from[axis]) / direction[axis];
+        let far = (max[axis] - from[axis]) / direction[axis];
+        enter = enter.max(near.min(far));
+        exit = exit.min(near.max(far));
+    }
+
+    enter <= exit
+}
+
+/// Whether the vision cones are shown.
+#[derive(Default, Resource)]
+pub struct VisionDebug {
+    pub enabled: bool,
+}
+
+#[derive(Resource)]
+struct ConeAssets {
+    mesh: Handle<Mesh>,
+    unaware: Handle<ColorMaterial>,
+    seeing: Handle<ColorMaterial>,
+}
+
+impl FromWorld for ConeAssets {
+    fn from_world(world: &mut World) -> Self {
+        // A fan of triangles around the origin, pointing along the x-axis.
+        let mut positions = vec![[0.0, 0.0, 0.0]];
+        for segment in 0..=CONE_SEGMENTS {
+            let angle = VISION_ANGLE * (segment as f32 / CONE_SEGMENTS as f32 - 0.5);
+            positions.push([VISION_RANGE * angle.cos(), VISION_RANGE * angle.sin(), 0.0]);
+        }
+        let indices = (1..=CONE_SEGMENTS as u32)
+            .flat_map(|segment| [0, segment, segment + 1])
+            .collect();
+
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_NORMAL,
+            vec![[0.0, 0.0, 1.0]; positions.len()],
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0, 0.0]; positions.len()]);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.set_indices(Some(Indices::U32(indices)));
+
+        let mesh = world.resource_mut::<Assets<Mesh>>().add(mesh);
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+
+        ConeAssets {
+            mesh,
+            unaware: materials.add(ColorMaterial::from(Color::rgba(1.0, 1.0, 1.0, 0.15))),
+            seeing: materials.add(ColorMaterial::from(Color::rgba(1.0, 0.0, 0.0, 0.3))),
+        }
+    }
+}
+
+#[derive(Component)]
+struct VisionCone;
+
+pub struct VisionPlugin;
+
+impl Plugin for VisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<VisionDebug>()
+            .init_resource::<ConeAssets>()
+            .add_system(toggle_debug)
+            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(draw_cones))
+            .add_system_set(SystemSet::on_exit(GameState::InGame).with_system(cleanup_cones));
+    }
+}
+
+fn toggle_debug(keys: Res<Input<KeyCode>>, mut debug: ResMut<VisionDebug>) {
+    if keys.just_pressed(KeyCode::F3) {
+        debug.enabled = !debug.enabled;
+    }
+}
+
+/// Redraw the vision cone of every co-worker who is still looking for the elephant.
+fn draw_cones(
+    mut commands: Commands,
+    debug: Res<VisionDebug>,
+    cone_assets: Res<ConeAssets>,
+    line_of_sight: LineOfSight,
+    enemy_query: Query<(&Transform, &Moving), With<Enemy>>,
+    tower_query: Query<&Transform, With<Tower>>,
+    cone_query: Query<Entity, With<VisionCone>>,
+) {
+    for entity in &cone_query {
+        commands.entity(entity).despawn();
+    }
+
+    if !debug.enabled {
+        return;
+    }
+
+    for (transform, moving) in &enemy_query {
+        let facing = moving.velocity.truncate();
+        if moving.state != MovingState::Normal || facing == Vec2::ZERO {
+            continue;
+        }
+
+        let position = transform.translation.truncate();
+        let seeing = tower_query
+            .iter()
+            .any(|tower| line_of_sight.sees(position, facing, tower.translation.truncate()));
+
+        commands.spawn((
+            MaterialMesh2dBundle {
+                mesh: cone_assets.mesh.clone().into(),
+                material: match seeing {
+                    true => cone_assets.seeing.clone(),
+                    false => cone_assets.unaware.clone(),
+                },
+                transform: Transform::from_translation(position.extend(0.5))
+                    .with_rotation(Quat::from_rotation_z(Vec2::X.angle_between(facing))),
+                ..default()
+            },
+            VisionCone,
+        ));
+    }
+}
+
+fn cleanup_cones(mut commands: Commands, cone_query: Query<Entity, With<VisionCone>>) {
+    for entity in &cone_query {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RIGHT: Vec2 = Vec2::X;
+
+    #[test]
+    fn sees_targets_ahead() {
+        assert!(in_cone(Vec2::ZERO, RIGHT, Vec2::new(100.0, 0.0)));
+        assert!(in_cone(Vec2::ZERO, RIGHT, Vec2::new(100.0, 80.0)));
+    }
+
+    #[test]
+    fn does_not_see_targets_outside_the_angle() {
+        assert!(!in_cone(Vec2::ZERO, RIGHT, Vec2::new(80.0, 100.0)));
+        assert!(!in_cone(Vec2::ZERO, RIGHT, Vec2::new(-100.0, 0.0)));
+    }
+
+    #[test]
+    fn does_not_see_targets_out_of_range() {
+        assert!(!in_cone(
+            Vec2::ZERO,
+            RIGHT,
+            Vec2::new(VISION_RANGE + 1.0, 0.0)
+        ));
+    }
+
+    #[test]
+    fn standing_still_sees_nothing() {
+        assert!(!in_cone(Vec2::ZERO, Vec2::ZERO, Vec2::new(100.0, 0.0)));
+    }
+
+    #[test]
+    fn segment_crossing_a_box_intersects() {
+        let center = Vec2::new(50.0, 50.0);
+        let size = Vec2::splat(20.0);
+
+        assert!(intersects(Vec2::ZERO, Vec2::splat(100.0), center, size));
+        assert!(intersects(Vec2::splat(100.0), Vec2::ZERO, center, size));
+    }
+
+    #[test]
+    fn segment_touching_a_box_intersects() {
+        let center = Vec2::new(50.0, 10.0);
+        let size = Vec2::splat(20.0);
+
+        assert!(intersects(Vec2::ZERO, Vec2::new(100.0, 0.0), center, size));
+        assert!(intersects(Vec2::ZERO, Vec2::new(40.0, 0.0), center, size));
+    }
+
+    #[test]
+    fn segment_missing_a_box_does_not_intersect() {
+        let center = Vec2::new(50.0, 50.0);
+        let size = Vec2::splat(20.0);
+
+        // Passing beside the box.
+        assert!(!intersects(
+            Vec2::ZERO,
+            Vec2::new(100.0, 20.0),
+            center,
+            size
+        ));
+        // Ending before the box.
+        assert!(!intersects(Vec2::ZERO, Vec2::splat(30.0), center, size));
+    }
+
+    #[test]
+    fn axis_parallel_segments() {
+        let center = Vec2::new(50.0, 0.0);
+        let size = Vec2::splat(20.0);
+
+        assert!(intersects(Vec2::ZERO, Vec2::new(100.0, 0.0), center, size));
+        assert!(intersects(
+            Vec2::new(50.0, -50.0),
+            Vec2::new(50.0, 50.0),
+            center,
+            size
+        ));
+        assert!(!intersects(
+            Vec2::new(0.0, 20.0),
+            Vec2::new(100.0, 20.0),
+            center,
+            size
+        ));
+        assert!(!intersects(
+            Vec2::new(0.0, -50.0),
+            Vec2::new(0.0, 50.0),
+            center,
+            size
+        ));
+    }
+}
@@ -1,14 +1,33 @@
 use bevy::prelude::*;
-use bevy_kira_audio::prelude::{Audio, *};
 use rand::Rng;
 use std::time::Duration;
 
-use crate::enemies::Enemy;
+use crate::audio::SoundEffects;
+use crate::camera::PLAY_AREA;
+use crate::enemies::{Enemy, EnemyLeftEvent};
 use crate::force::{Force, ForceType};
 use crate::game::{GameState, Volatile};
-use crate::level::Level;
+use crate::level::{Arrival, Level, LevelReloadedEvent};
+use crate::localization::Localization;
 use crate::physics::{Collider, ColliderBundle, Moving};
+use crate::simulation::{
+    simulation_step, Interpolated, SimulationPhase, SimulationRng, SimulationStage, SimulationTime,
+};
 use crate::sprite::AnimationTimer;
+use crate::tower::discover_tower;
+use crate::ui::BAR_WIDTH;
+
+/// How long before a co-worker arrives their entrance is highlighted.
+const ENTRANCE_WARNING: Duration = Duration::from_secs(2);
+/// Distance from the edge of the play area to the warning of an entrance.
+const WARNING_MARGIN: f32 = 40.0;
+
+/// Amount of co-workers listed in the panel of upcoming ones.
+const UPCOMING_COUNT: usize = 4;
+/// Co-workers are shown in the panel at this multiple of the size of their sprite.
+const PORTRAIT_SCALE: f32 = 2.0;
+/// Frames in the spritesheets of co-workers, of which the panel only shows the first.
+const SPRITE_FRAMES: f32 = 6.0;
 
 struct EnemySpawnEvent {
     influence: f32,
@@ -17,6 +36,8 @@ struct EnemySpawnEvent {
     sprite: String,
     sprite_size: Vec2,
     location: Vec3,
+    /// Direction from the entrance onto the floor.
+    direction: Vec2,
 }
 
 #[derive(Debug)]
@@ -27,13 +48,12 @@ pub struct EnemySpawn {
     attention_span: u64,
     sprite: String,
     sprite_size: Vec2,
-    location: Vec3,
+    /// Index of the entrance of the level the co-worker comes through.
+    entrance: usize,
 }
 
 impl Default for EnemySpawn {
     fn default() -> Self {
-        let height = rand::thread_rng().gen_range(-36.0..36.0);
-
         EnemySpawn {
             spawn_timer: Timer::new(Duration::from_secs(0), TimerMode::Once),
             influence: 80.0,
@@ -41,7 +61,20 @@ impl Default for EnemySpawn {
             attention_span: 15,
             sprite: "sprites/spritesheet_NPC01_M_walk.png".into(),
             sprite_size: Vec2::new(16.0, 24.0),
-            location: Vec3::new(-640.0 - 4.0 * 12.0, height, 0.0),
+            entrance: 0,
+        }
+    }
+}
+
+impl From<&Arrival> for EnemySpawn {
+    fn from(arrival: &Arrival) -> Self {
+        EnemySpawn {
+            spawn_timer: Timer::from_seconds(arrival.seconds, TimerMode::Once),
+            force_type: arrival.force_type,
+            sprite: arrival.sprite.clone(),
+            sprite_size: arrival.sprite_size,
+            entrance: arrival.entrance,
+            ..default()
         }
     }
 }
@@ -49,6 +82,39 @@ impl Default for EnemySpawn {
 #[derive(Debug, Default, Resource)]
 struct EnemySpawnQueue {
     pub enemies: Vec<EnemySpawn>,
+    /// Time since the start of the floor.
+    elapsed: Duration,
+}
+
+/// Keeps count of the co-workers on the current floor.
+#[derive(Debug, Default, Resource)]
+pub struct WaveProgress {
+    pub spawned: u32,
+    /// Co-workers who lost interest and left the floor.
+    pub distracted: u32,
+}
+
+#[derive(Component)]
+struct ProgressText;
+
+/// Panel next to the UI bar which lists the next co-workers to arrive.
+#[derive(Component)]
+struct UpcomingPanel;
+
+/// A row of the upcoming co-workers panel, with the position in the queue it shows.
+#[derive(Component)]
+struct UpcomingRow(usize);
+
+#[derive(Component)]
+struct UpcomingPortrait(usize);
+
+#[derive(Component)]
+struct UpcomingInfo(usize);
+
+/// Flashes while a co-worker is about to come through an entrance.
+#[derive(Component)]
+struct EntranceWarning {
+    entrance: usize,
 }
 
 pub struct WavePlugin;
@@ -56,130 +122,176 @@ pub struct WavePlugin;
 impl Plugin for WavePlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(EnemySpawnQueue::default())
+            .insert_resource(WaveProgress::default())
             .add_event::<EnemySpawnEvent>()
-            .add_system_set(SystemSet::on_enter(GameState::InGame).with_system(setup_wave))
-            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(tick_wave))
-            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(spawn_enemy))
-            .add_system_set(SystemSet::on_exit(GameState::InGame).with_system(cleanup_wave));
+            .add_system_set(
+                SystemSet::on_enter(GameState::InGame)
+                    .with_system(setup_wave)
+                    .with_system(setup_text)
+                    .with_system(setup_entrance_warnings)
+                    .with_system(setup_upcoming_panel),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame).with_system(update_entrance_warnings),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame).with_system(update_upcoming_panel),
+            )
+            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(reload_arrivals))
+            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(count_distracted))
+            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(update_text))
+            .add_system_set(
+                // Losing wins over winning when both happen in the same frame.
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(finish_wave.after(discover_tower)),
+            )
+            .add_system_set_to_stage(
+                SimulationStage,
+                simulation_step(SimulationPhase::Arrive)
+                    .with_system(tick_wave)
+                    .with_system(spawn_enemy.after(tick_wave)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::InGame)
+                    .with_system(cleanup_wave)
+                    .with_system(cleanup_upcoming_panel),
+            );
     }
 }
 
-fn setup_wave(level: Res<Level>, mut enemy_queue: ResMut<EnemySpawnQueue>) {
-    match level.level {
-        1 => {
-            enemy_queue.enemies.push(EnemySpawn {
-                spawn_timer: Timer::new(Duration::from_secs(3), TimerMode::Once),
-                sprite: "sprites/spritesheet_NPC01_M_walk.png".into(),
-                ..default()
-            });
-            enemy_queue.enemies.push(EnemySpawn {
-                spawn_timer: Timer::new(Duration::from_secs(4), TimerMode::Once),
-                sprite: "sprites/spritesheet_NPC02_M_walk.png".into(),
-                ..default()
-            });
-            enemy_queue.enemies.push(EnemySpawn {
-                spawn_timer: Timer::new(Duration::from_secs(20), TimerMode::Once),
-                sprite: "sprites/spritesheet_NPC02_M_walk.png".into(),
-                ..default()
-            });
-        }
-        2 => {
-            enemy_queue.enemies.push(EnemySpawn {
-                spawn_timer: Timer::new(Duration::from_secs(3), TimerMode::Once),
-                sprite: "sprites/spritesheet_NPC03_M_walk.png".into(),
-                ..default()
-            });
-            enemy_queue.enemies.push(EnemySpawn {
-                spawn_timer: Timer::new(Duration::from_secs(4), TimerMode::Once),
-                sprite: "sprites/spritesheet_NPC01_M_walk.png".into(),
-                ..default()
-            });
-            enemy_queue.enemies.push(EnemySpawn {
-                spawn_timer: Timer::new(Duration::from_secs(9), TimerMode::Once),
-                sprite: "sprites/spritesheet_NPC02_M_walk.png".into(),
-                ..default()
-            });
-            enemy_queue.enemies.push(EnemySpawn {
-                spawn_timer: Timer::new(Duration::from_secs(20), TimerMode::Once),
-                sprite: "sprites/spritesheet_NPC03_M_walk.png".into(),
-                ..default()
-            });
-        }
-        3 => {
-            enemy_queue.enemies.push(EnemySpawn {
-                spawn_timer: Timer::new(Duration::from_secs(3), TimerMode::Once),
-                sprite: "sprites/spritesheet_NPC03_M_walk.png".into(),
-                ..default()
-            });
-            enemy_queue.enemies.push(EnemySpawn {
-                spawn_timer: Timer::new(Duration::from_secs(4), TimerMode::Once),
-                sprite: "sprites/spritesheet_NPC01_M_walk.png".into(),
-                ..default()
-            });
-            enemy_queue.enemies.push(EnemySpawn {
-                spawn_timer: Timer::new(Duration::from_secs(10), TimerMode::Once),
-                sprite: "sprites/spritesheet_NPC04_M_walk.png".into(),
-                sprite_size: Vec2::new(16.0, 32.0),
-                force_type: ForceType::Repel,
-                ..default()
-            });
-            enemy_queue.enemies.push(EnemySpawn {
-                spawn_timer: Timer::new(Duration::from_secs(20), TimerMode::Once),
-                sprite: "sprites/spritesheet_NPC03_M_walk.png".into(),
-                ..default()
-            });
-        }
-        4 => {
-            enemy_queue.enemies.push(EnemySpawn {
-                spawn_timer: Timer::new(Duration::from_secs(5), TimerMode::Once),
-                sprite: "sprites/spritesheet_NPC04_M_walk.png".into(),
-                sprite_size: Vec2::new(16.0, 32.0),
-                force_type: ForceType::Repel,
-                ..default()
-            });
-            for _ in 1..20 {
-                enemy_queue.enemies.push(EnemySpawn {
-                    spawn_timer: Timer::new(Duration::from_secs(12), TimerMode::Once),
-                    sprite: "sprites/spritesheet_NPC03_M_walk.png".into(),
-                    ..default()
-                });
-            }
-            enemy_queue.enemies.push(EnemySpawn {
-                spawn_timer: Timer::new(Duration::from_secs(30), TimerMode::Once),
+fn setup_wave(
+    level: Res<Level>,
+    mut enemy_queue: ResMut<EnemySpawnQueue>,
+    mut progress: ResMut<WaveProgress>,
+) {
+    *progress = WaveProgress::default();
+
+    enemy_queue.elapsed = Duration::ZERO;
+    enemy_queue
+        .enemies
+        .extend(level.layout.arrivals.iter().map(EnemySpawn::from));
+}
+
+/// Take the co-workers who haven't arrived yet from the level file again when it has changed.
+fn reload_arrivals(
+    mut ev_reloaded: EventReader<LevelReloadedEvent>,
+    level: Res<Level>,
+    mut enemy_queue: ResMut<EnemySpawnQueue>,
+) {
+    if ev_reloaded.iter().count() == 0 {
+        return;
+    }
+
+    let elapsed = enemy_queue.elapsed;
+    enemy_queue.enemies = level
+        .layout
+        .arrivals
+        .iter()
+        .filter(|arrival| arrival.seconds > elapsed.as_secs_f32())
+        .map(|arrival| {
+            let mut enemy_spawn = EnemySpawn::from(arrival);
+            enemy_spawn.spawn_timer.set_elapsed(elapsed);
+            enemy_spawn
+        })
+        .collect();
+}
+
+fn setup_text(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: asset_server.load("fonts/PixeloidSans.ttf"),
+                font_size: 20.0,
+                color: Color::rgba(0.9, 0.9, 0.9, 0.7),
+            },
+        )
+        .with_text_alignment(TextAlignment::TOP_RIGHT)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                top: Val::Px(50.0),
+                right: Val::Px(5.0),
                 ..default()
-            });
-        }
-        _ => {}
+            },
+            ..default()
+        }),
+        ProgressText,
+        Volatile,
+    ));
+}
+
+fn count_distracted(
+    mut ev_enemy_left: EventReader<EnemyLeftEvent>,
+    mut progress: ResMut<WaveProgress>,
+) {
+    for _ in ev_enemy_left.iter() {
+        progress.distracted += 1;
     }
 }
 
-fn tick_wave(
+fn update_text(
+    progress: Res<WaveProgress>,
+    enemy_queue: Res<EnemySpawnQueue>,
+    localization: Res<Localization>,
+    mut text_query: Query<&mut Text, With<ProgressText>>,
+) {
+    let mut text = text_query.single_mut();
+    let total = progress.spawned as usize + enemy_queue.enemies.len();
+    text.sections[0].value = localization.format(
+        "wave.progress",
+        &[("distracted", &progress.distracted), ("total", &total)],
+    );
+}
+
+/// The floor is won once every co-worker has arrived and left again.
+fn finish_wave(
     level: Res<Level>,
     mut game_state: ResMut<State<GameState>>,
-    mut enemy_queue: ResMut<EnemySpawnQueue>,
-    mut ev_spawn_enemy: EventWriter<EnemySpawnEvent>,
-    time: Res<Time>,
+    enemy_queue: Res<EnemySpawnQueue>,
+    progress: Res<WaveProgress>,
 ) {
-    if enemy_queue.enemies.is_empty() {
-        if level.level == 4 {
-            game_state.set(GameState::End).unwrap();
-        } else {
-            game_state.set(GameState::Won).unwrap();
-        }
+    if enemy_queue.enemies.is_empty() && progress.distracted >= progress.spawned {
+        // Test-plays of the last floor return to the editor as well.
+        let next = match level.level == 4 && !level.test_play {
+            true => GameState::End,
+            false => GameState::Won,
+        };
+        // Fails when the floor has been lost this frame, or another state has been queued.
+        let _ = game_state.set(next);
     }
+}
 
-    let time_delta = time.delta();
+fn tick_wave(
+    mut enemy_queue: ResMut<EnemySpawnQueue>,
+    mut ev_spawn_enemy: EventWriter<EnemySpawnEvent>,
+    simulation_time: Res<SimulationTime>,
+    level: Res<Level>,
+    mut rng: ResMut<SimulationRng>,
+) {
+    let time_delta = simulation_time.delta();
+    enemy_queue.elapsed += time_delta;
 
     enemy_queue.enemies.retain_mut(|enemy_spawn| {
         enemy_spawn.spawn_timer.tick(time_delta);
         if enemy_spawn.spawn_timer.finished() {
+            let entrance = match level.entrance(enemy_spawn.entrance) {
+                Some(entrance) => entrance,
+                None => {
+                    warn!("No entrance for a co-worker to arrive through");
+                    return false;
+                }
+            };
+            let spread = entrance.inward().perp() * rng.gen_range(-36.0..36.0);
+
             ev_spawn_enemy.send(EnemySpawnEvent {
                 influence: enemy_spawn.influence,
                 force_type: enemy_spawn.force_type,
                 attention_span: enemy_spawn.attention_span,
                 sprite: enemy_spawn.sprite.clone(),
                 sprite_size: enemy_spawn.sprite_size,
-                location: enemy_spawn.location,
+                location: (entrance.position + spread).extend(0.0),
+                direction: entrance.inward(),
             });
 
             false
@@ -194,18 +306,20 @@ fn spawn_enemy(
     mut ev_spawn_enemy: EventReader<EnemySpawnEvent>,
     asset_server: Res<AssetServer>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
-    audio: Res<Audio>,
+    mut progress: ResMut<WaveProgress>,
+    sound_effects: SoundEffects,
+    mut rng: ResMut<SimulationRng>,
 ) {
-    let mut rng = rand::thread_rng();
-
     for ev in ev_spawn_enemy.iter() {
+        progress.spawned += 1;
+
         let texture_handle = asset_server.load(&ev.sprite);
         let texture_atlas =
             TextureAtlas::from_grid(texture_handle, ev.sprite_size, 6, 1, None, None);
         let texture_atlas_handle = texture_atlases.add(texture_atlas);
 
         let moving_delta = rng.gen_range(-25.0..25.0);
-        let moving = Moving::new(Vec3::new(150.0 + moving_delta, 0.0, 0.0));
+        let moving = Moving::new((ev.direction * (150.0 + moving_delta)).extend(0.0));
 
         let mut color = Color::from(ev.force_type);
         color.set_a(match ev.force_type {
@@ -232,7 +346,7 @@ fn spawn_enemy(
         };
 
         if let Some(s) = sound {
-            audio.play(s);
+            sound_effects.play_at(s, ev.location);
         }
 
         commands.spawn((
@@ -256,6 +370,7 @@ fn spawn_enemy(
             Enemy {
                 timer: Timer::new(Duration::from_secs(ev.attention_span), TimerMode::Once),
             },
+            Interpolated::new(ev.location),
             Volatile,
             Force {
                 newton: 500.0,
@@ -266,6 +381,222 @@ fn spawn_enemy(
     }
 }
 
+fn setup_entrance_warnings(
+    mut commands: Commands,
+    level: Res<Level>,
+    localization: Res<Localization>,
+    asset_server: Res<AssetServer>,
+) {
+    // Keep the warnings on screen and out from under the UI bar.
+    let max = PLAY_AREA / 2.0 - WARNING_MARGIN;
+    let min = -max + Vec2::new(BAR_WIDTH, 0.0);
+
+    for (index, entrance) in level.entrances().iter().enumerate() {
+        let name = localization.get(entrance.name());
+
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    localization.format("entrance.warning", &[("entrance", &name)]),
+                    TextStyle {
+                        font: asset_server.load("fonts/PixeloidSans.ttf"),
+                        font_size: 24.0,
+                        color: Color::rgb(1.0, 0.8, 0.2),
+                    },
+                )
+                .with_alignment(TextAlignment::CENTER),
+                transform: Transform::from_translation(
+                    entrance.position.clamp(min, max).extend(1.0),
+                ),
+                visibility: Visibility { is_visible: false },
+                ..default()
+            },
+            EntranceWarning { entrance: index },
+            Volatile,
+        ));
+    }
+}
+
+fn update_entrance_warnings(
+    enemy_queue: Res<EnemySpawnQueue>,
+    time: Res<Time>,
+    mut warning_query: Query<(&EntranceWarning, &mut Visibility, &mut Text)>,
+) {
+    for (warning, mut visibility, mut text) in &mut warning_query {
+        visibility.is_visible = enemy_queue.enemies.iter().any(|enemy_spawn| {
+            enemy_spawn.entrance == warning.entrance
+                && enemy_spawn.spawn_timer.remaining() <= ENTRANCE_WARNING
+        });
+
+        let alpha = 0.6 + 0.4 * (time.elapsed_seconds() * 8.0).sin();
+        text.sections[0].style.color.set_a(alpha);
+    }
+}
+
+fn setup_upcoming_panel(
+    mut commands: Commands,
+    localization: Res<Localization>,
+    asset_server: Res<AssetServer>,
+) {
+    let font = asset_server.load("fonts/PixeloidSans.ttf");
+    let text_style = TextStyle {
+        font,
+        font_size: 16.0,
+        color: Color::rgba(0.9, 0.9, 0.9, 0.9),
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Px(BAR_WIDTH + 5.0),
+                        top: Val::Px(5.0),
+                        ..default()
+                    },
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(5.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.4).into(),
+                ..default()
+            },
+            UpcomingPanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn(TextBundle::from_section(
+                localization.get("upcoming.title"),
+                text_style.clone(),
+            ));
+
+            for index in 0..UPCOMING_COUNT {
+                panel
+                    .spawn((
+                        NodeBundle {
+                            style: Style {
+                                align_items: AlignItems::Center,
+                                margin: UiRect::top(Val::Px(5.0)),
+                                padding: UiRect::all(Val::Px(2.0)),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        UpcomingRow(index),
+                    ))
+                    .with_children(|row| {
+                        // Only the first frame of the spritesheet fits within the portrait.
+                        row.spawn(NodeBundle {
+                            style: Style {
+                                size: Size::new(
+                                    Val::Px(16.0 * PORTRAIT_SCALE),
+                                    Val::Px(32.0 * PORTRAIT_SCALE),
+                                ),
+                                align_items: AlignItems::FlexEnd,
+                                overflow: Overflow::Hidden,
+                                margin: UiRect::right(Val::Px(5.0)),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|portrait| {
+                            portrait.spawn((ImageBundle::default(), UpcomingPortrait(index)));
+                        });
+
+                        row.spawn((
+                            TextBundle::from_section("", text_style.clone()),
+                            UpcomingInfo(index),
+                        ));
+                    });
+            }
+        });
+}
+
+/// Show the co-workers who arrive first, with their kind, entrance and time until they arrive.
+///
+/// The queue changes on every step, so only what is shown differently is updated, to not lay out
+/// the panel again every frame.
+fn update_upcoming_panel(
+    enemy_queue: Res<EnemySpawnQueue>,
+    level: Res<Level>,
+    localization: Res<Localization>,
+    asset_server: Res<AssetServer>,
+    mut row_query: Query<(&UpcomingRow, &mut Style, &mut BackgroundColor)>,
+    mut portrait_query: Query<(&UpcomingPortrait, &mut UiImage, &mut Style), Without<UpcomingRow>>,
+    mut info_query: Query<(&UpcomingInfo, &mut Text)>,
+) {
+    let mut upcoming: Vec<&EnemySpawn> = enemy_queue.enemies.iter().collect();
+    upcoming.sort_by_key(|enemy_spawn| enemy_spawn.spawn_timer.remaining());
+
+    for (row, mut style, mut background) in &mut row_query {
+        let display = match upcoming.get(row.0) {
+            Some(_) => Display::Flex,
+            None => Display::None,
+        };
+        if style.display != display {
+            style.display = display;
+        }
+
+        if let Some(enemy_spawn) = upcoming.get(row.0) {
+            let mut color = Color::from(enemy_spawn.force_type);
+            color.set_a(0.4);
+            if background.0 != color {
+                background.0 = color;
+            }
+        }
+    }
+
+    for (portrait, mut image, mut style) in &mut portrait_query {
+        if let Some(enemy_spawn) = upcoming.get(portrait.0) {
+            let shown = asset_server
+                .get_handle_path(&image.0)
+                .is_some_and(|path| path.path().to_str() == Some(&enemy_spawn.sprite));
+            if !shown {
+                image.0 = asset_server.load(&enemy_spawn.sprite);
+            }
+
+            let size = enemy_spawn.sprite_size * PORTRAIT_SCALE;
+            let size = Size::new(Val::Px(size.x * SPRITE_FRAMES), Val::Px(size.y));
+            if style.size != size {
+                style.size = size;
+            }
+        }
+    }
+
+    for (info, mut text) in &mut info_query {
+        if let Some(enemy_spawn) = upcoming.get(info.0) {
+            let kind = localization.get(match enemy_spawn.force_type {
+                ForceType::Passive => "upcoming.passive",
+                ForceType::Attract => "upcoming.attract",
+                ForceType::Repel => "upcoming.repel",
+            });
+            // Co-workers without an entrance are skipped once they are due.
+            let entrance = match level.entrance(enemy_spawn.entrance) {
+                Some(entrance) => localization.get(entrance.name()),
+                None => continue,
+            };
+
+            let value = localization.format(
+                "upcoming.entry",
+                &[
+                    ("kind", &kind),
+                    ("entrance", &entrance),
+                    ("seconds", &enemy_spawn.spawn_timer.remaining_secs().ceil()),
+                ],
+            );
+            if text.sections[0].value != value {
+                text.sections[0].value = value;
+            }
+        }
+    }
+}
+
+fn cleanup_upcoming_panel(mut commands: Commands, panel_query: Query<Entity, With<UpcomingPanel>>) {
+    for entity in &panel_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
 fn cleanup_wave(mut enemy_queue: ResMut<EnemySpawnQueue>) {
     enemy_queue.enemies.clear();
 }